@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::types::{Link, LinkGroups};
+
+// Entries ending with `*` match any parameter starting with the rest of the entry
+const DEFAULT_TRACKING_PARAMS: [&str; 12] = [
+    "utm_*", "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "mc_cid",
+    "mc_eid", "igshid", "_ga",
+];
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CanonicalizeConfig {
+    pub tracking_params: Vec<String>,
+}
+
+impl Default for CanonicalizeConfig {
+    fn default() -> Self {
+        CanonicalizeConfig {
+            tracking_params: DEFAULT_TRACKING_PARAMS
+                .iter()
+                .map(|param| param.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl CanonicalizeConfig {
+    fn is_tracking_param(&self, key: &str) -> bool {
+        self.tracking_params
+            .iter()
+            .any(|param| match param.strip_suffix('*') {
+                Some(prefix) => key.starts_with(prefix),
+                None => key == param,
+            })
+    }
+
+    // Builds the key used to compare links when looking for duplicates. The result
+    // is only meant for comparisons and is not guaranteed to be a loadable URI
+    pub fn canonicalize(&self, uri: &str) -> String {
+        let uri = uri.trim();
+
+        let Ok(url) = Url::parse(uri) else {
+            return uri.to_string();
+        };
+
+        let Some(host) = url.host_str() else {
+            let without_fragment = url.as_str().split('#').next().unwrap_or_default();
+
+            return normalize_percent_encoding(without_fragment);
+        };
+
+        let mut key = String::with_capacity(uri.len());
+
+        // http and https almost always serve the same page, so the scheme is only
+        // kept for anything else
+        if !matches!(url.scheme(), "http" | "https") {
+            key.push_str(url.scheme());
+            key.push_str("://");
+        }
+
+        // Hosts are already lowercased by the parser
+        key.push_str(host.strip_prefix("www.").unwrap_or(host));
+
+        // Default ports are already dropped by the parser
+        if let Some(port) = url.port() {
+            key.push_str(&format!(":{}", port));
+        }
+
+        key.push_str(&normalize_percent_encoding(
+            url.path().trim_end_matches('/'),
+        ));
+
+        let query = url
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .filter(|pair| {
+                let name = pair.split('=').next().unwrap_or_default();
+
+                !self.is_tracking_param(&name.to_lowercase())
+            })
+            .map(normalize_percent_encoding)
+            .collect::<Vec<_>>();
+
+        if !query.is_empty() {
+            key.push('?');
+            key.push_str(&query.join("&"));
+        }
+
+        key
+    }
+
+    // Ids of the links under each canonical key, built once so that the duplicates
+    // of many links can be looked up
+    pub fn index(&self, link_groups: &LinkGroups) -> BTreeMap<String, Vec<usize>> {
+        let mut index = BTreeMap::<String, Vec<usize>>::new();
+
+        for link in link_groups.iter().flat_map(|group| &group.links) {
+            index
+                .entry(self.canonicalize(&link.uri))
+                .or_default()
+                .push(link.id);
+        }
+
+        index
+    }
+
+    // Ids of the other links in `index` that count as duplicates of `link`
+    pub fn duplicates_of(&self, index: &BTreeMap<String, Vec<usize>>, link: &Link) -> Vec<usize> {
+        index
+            .get(&self.canonicalize(&link.uri))
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|id| *id != link.id)
+            .collect()
+    }
+}
+
+// Decodes escaped unreserved characters and uppercases the hex digits of any
+// escape that has to stay, so that `%7e`, `%7E` and `~` all compare equal
+fn normalize_percent_encoding(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut result = String::with_capacity(value.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = if bytes[i] == b'%' {
+            value
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+
+        match escaped {
+            Some(byte) if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) => {
+                result.push(byte as char);
+                i += 3;
+            }
+            Some(byte) => {
+                result.push_str(&format!("%{:02X}", byte));
+                i += 3;
+            }
+            None => {
+                let char = value[i..]
+                    .chars()
+                    .next()
+                    .expect("Index is on a char boundary");
+
+                result.push(char);
+                i += char.len_utf8();
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::LinkGroup;
+
+    fn canonicalize(uri: &str) -> String {
+        CanonicalizeConfig::default().canonicalize(uri)
+    }
+
+    fn link(uri: &str) -> Link {
+        Link::new(uri.to_string(), uri.to_string(), Vec::new())
+    }
+
+    #[test]
+    fn ignores_scheme_www_trailing_slash_and_fragment() {
+        let key = canonicalize("https://example.com/page");
+
+        assert_eq!(key, "example.com/page");
+        assert_eq!(canonicalize("http://www.example.com/page/"), key);
+        assert_eq!(canonicalize("https://EXAMPLE.com/page#section"), key);
+        assert_eq!(canonicalize("https://example.com:443/page"), key);
+        assert_eq!(canonicalize("  https://example.com/page  "), key);
+    }
+
+    #[test]
+    fn keeps_paths_case_and_other_ports() {
+        assert_ne!(
+            canonicalize("https://example.com/Page"),
+            canonicalize("https://example.com/page")
+        );
+        assert_eq!(
+            canonicalize("https://example.com:8080/page"),
+            "example.com:8080/page"
+        );
+    }
+
+    #[test]
+    fn keeps_schemes_other_than_http() {
+        assert_eq!(
+            canonicalize("ftp://example.com/file"),
+            "ftp://example.com/file"
+        );
+    }
+
+    #[test]
+    fn drops_tracking_params() {
+        assert_eq!(
+            canonicalize("https://example.com/?utm_source=feed&id=1&UTM_Medium=x&fbclid=abc"),
+            "example.com?id=1"
+        );
+        assert_eq!(
+            canonicalize("https://example.com/page?utm_campaign=a"),
+            "example.com/page"
+        );
+    }
+
+    #[test]
+    fn keeps_the_order_of_other_params() {
+        assert_ne!(
+            canonicalize("https://example.com/?a=1&b=2"),
+            canonicalize("https://example.com/?b=2&a=1")
+        );
+    }
+
+    #[test]
+    fn uses_the_configured_tracking_params() {
+        let config = CanonicalizeConfig {
+            tracking_params: vec!["ref".to_string(), "src_*".to_string()],
+        };
+
+        assert_eq!(
+            config.canonicalize("https://example.com/?ref=home&src_a=1&utm_source=feed"),
+            "example.com?utm_source=feed"
+        );
+    }
+
+    #[test]
+    fn normalizes_percent_encoding() {
+        let key = canonicalize("https://example.com/~user/a%2fb");
+
+        assert_eq!(canonicalize("https://example.com/%7euser/a%2Fb"), key);
+        assert_eq!(canonicalize("https://example.com/%7Euser/a%2fb"), key);
+        assert_eq!(key, "example.com/~user/a%2Fb");
+    }
+
+    #[test]
+    fn leaves_unparsable_uris_as_they_are() {
+        assert_eq!(canonicalize(" not a uri "), "not a uri");
+    }
+
+    #[test]
+    fn finds_duplicates_through_the_index() {
+        let first = link("https://example.com/page?utm_source=feed");
+        let second = link("http://www.example.com/page/");
+        let other = link("https://example.com/other");
+
+        let ids = [first.id, second.id, other.id];
+
+        let link_groups = LinkGroups::from([
+            LinkGroup::new([first.clone(), other.clone()].into()),
+            LinkGroup::new([second.clone()].into()),
+        ]);
+
+        let config = CanonicalizeConfig::default();
+        let index = config.index(&link_groups);
+
+        assert_eq!(config.duplicates_of(&index, &first), vec![ids[1]]);
+        assert_eq!(config.duplicates_of(&index, &second), vec![ids[0]]);
+        assert!(config.duplicates_of(&index, &other).is_empty());
+    }
+}
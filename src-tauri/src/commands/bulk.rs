@@ -0,0 +1,485 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::{
+    history::History,
+    tag_tree,
+    types::{get_new_id, HistoryContainer, Link, LinkGroups, LinksContainer, Tags, TagsContainer},
+};
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkOperation {
+    Remove,
+    // Links are placed in the order their ids were given, at the end of the group
+    // when there's no position
+    Move {
+        group_id: usize,
+        position: Option<usize>,
+    },
+    Copy {
+        group_id: usize,
+        position: Option<usize>,
+    },
+    AddTags {
+        tags: Vec<String>,
+    },
+    RemoveTags {
+        tags: Vec<String>,
+    },
+}
+
+impl BulkOperation {
+    fn takes_links_out(&self) -> bool {
+        matches!(self, BulkOperation::Remove | BulkOperation::Move { .. })
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            BulkOperation::Remove => "Remove links",
+            BulkOperation::Move { .. } => "Move links",
+            BulkOperation::Copy { .. } => "Copy links",
+            BulkOperation::AddTags { .. } => "Add tags",
+            BulkOperation::RemoveTags { .. } => "Remove tags",
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct BulkFailure {
+    id: usize,
+    error: String,
+}
+
+#[derive(Serialize, Default)]
+pub struct BulkResult {
+    succeeded: Vec<usize>,
+    failed: Vec<BulkFailure>,
+}
+
+fn find_link(link_groups: &LinkGroups, id: usize) -> Option<(usize, usize)> {
+    link_groups
+        .iter()
+        .enumerate()
+        .find_map(|(group_pos, group)| {
+            group
+                .links
+                .iter()
+                .position(|link| link.id == id)
+                .map(|link_pos| (group_pos, link_pos))
+        })
+}
+
+fn group_position(link_groups: &LinkGroups, group_id: usize) -> Result<usize, String> {
+    link_groups
+        .iter()
+        .position(|group| group.id == group_id)
+        .ok_or(format!("Group {} wasn't found", group_id))
+}
+
+// Applies the operation to every link in one go, recording a single undo entry.
+// Problems with the operation itself fail the whole call without changing anything,
+// while links that can't be found are listed in the result
+#[tauri::command]
+pub fn bulk_edit_links(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    link_ids: Vec<usize>,
+    operation: BulkOperation,
+) -> Result<BulkResult, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    edit_links(
+        &mut link_groups,
+        &mut tags,
+        &mut history,
+        link_ids,
+        operation,
+    )
+}
+
+fn edit_links(
+    link_groups: &mut LinkGroups,
+    tags: &mut Tags,
+    history: &mut History,
+    link_ids: Vec<usize>,
+    operation: BulkOperation,
+) -> Result<BulkResult, String> {
+    let operation_tags = match operation {
+        BulkOperation::AddTags { tags: ref names }
+        | BulkOperation::RemoveTags { tags: ref names } => names
+            .iter()
+            .map(|name| tag_tree::normalize(name).map(|name| tag_tree::resolve(tags, &name)))
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => Vec::new(),
+    };
+
+    let target = match operation {
+        BulkOperation::Move { group_id, .. } | BulkOperation::Copy { group_id, .. } => {
+            let target = group_position(link_groups, group_id)?;
+
+            link_groups[target].check_unlocked()?;
+
+            target
+        }
+        _ => 0,
+    };
+
+    let mut result = BulkResult::default();
+    let mut ids = Vec::<usize>::with_capacity(link_ids.len());
+
+    for id in link_ids {
+        if ids.contains(&id) {
+            continue;
+        }
+
+        let error = match find_link(link_groups, id) {
+            Some((group_pos, _)) if operation.takes_links_out() => {
+                link_groups[group_pos].check_unlocked().err()
+            }
+            Some(_) => None,
+            None => Some(format!("Link {} wasn't found", id)),
+        };
+
+        match error {
+            Some(error) => result.failed.push(BulkFailure { id, error }),
+            None => ids.push(id),
+        }
+    }
+
+    if ids.is_empty() {
+        return Ok(result);
+    }
+
+    let recording = history.record(operation.label(), link_groups, tags);
+
+    match operation {
+        BulkOperation::Remove => {
+            for group in link_groups
+                .iter_mut()
+                .filter(|group| group.links.iter().any(|link| ids.contains(&link.id)))
+            {
+                group.links.retain(|link| !ids.contains(&link.id));
+                group.touch();
+            }
+        }
+        BulkOperation::Move { position, .. } | BulkOperation::Copy { position, .. } => {
+            let copy = matches!(operation, BulkOperation::Copy { .. });
+
+            let moved = ids
+                .iter()
+                .filter_map(|id| {
+                    let (group_pos, link_pos) = find_link(link_groups, *id)?;
+
+                    if copy {
+                        let link = link_groups[group_pos].links[link_pos].clone();
+
+                        // Snapshot files belong to one link, as removing or replacing
+                        // the snapshot deletes its file
+                        Some(Link {
+                            id: get_new_id(),
+                            snapshot: None,
+                            ..link
+                        })
+                    } else {
+                        link_groups[group_pos].touch();
+                        link_groups[group_pos].links.remove(link_pos)
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let group = &mut link_groups[target];
+
+            // Positions are within the group once the moved links were taken out of it
+            let position = position.unwrap_or(group.links.len()).min(group.links.len());
+
+            for (offset, link) in moved.into_iter().enumerate() {
+                group.links.insert(position + offset, link);
+            }
+
+            group.touch();
+        }
+        BulkOperation::AddTags { .. } => {
+            for link in link_groups
+                .iter_mut()
+                .flat_map(|group| group.links.iter_mut())
+                .filter(|link| ids.contains(&link.id))
+            {
+                for tag in operation_tags.iter() {
+                    if !link.tags.contains(tag) {
+                        link.tags.push(tag.to_owned());
+                    }
+                }
+
+                link.touch();
+            }
+
+            for tag in operation_tags {
+                tags.entry(tag).or_default();
+            }
+        }
+        BulkOperation::RemoveTags { .. } => {
+            for link in link_groups
+                .iter_mut()
+                .flat_map(|group| group.links.iter_mut())
+                .filter(|link| ids.contains(&link.id))
+            {
+                link.tags.retain(|tag| !operation_tags.contains(tag));
+                link.touch();
+            }
+        }
+    }
+
+    result.succeeded = ids;
+
+    history.finish(recording, link_groups, tags);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{LinkGroup, Snapshot, TagInfo};
+
+    struct Library {
+        link_groups: LinkGroups,
+        tags: Tags,
+        history: History,
+    }
+
+    impl Library {
+        // One group per entry, each with links to the given URIs
+        fn new(groups: &[&[&str]]) -> Library {
+            let link_groups = groups
+                .iter()
+                .map(|uris| {
+                    LinkGroup::new(
+                        uris.iter()
+                            .map(|uri| Link::new(uri.to_string(), uri.to_string(), Vec::new()))
+                            .collect(),
+                    )
+                })
+                .collect();
+
+            Library {
+                link_groups,
+                tags: Tags::new(),
+                history: History::default(),
+            }
+        }
+
+        fn id(&self, group: usize, link: usize) -> usize {
+            self.link_groups[group].links[link].id
+        }
+
+        fn uris(&self, group: usize) -> Vec<&str> {
+            self.link_groups[group]
+                .links
+                .iter()
+                .map(|link| link.uri.as_str())
+                .collect()
+        }
+
+        fn edit(
+            &mut self,
+            link_ids: Vec<usize>,
+            operation: BulkOperation,
+        ) -> Result<BulkResult, String> {
+            edit_links(
+                &mut self.link_groups,
+                &mut self.tags,
+                &mut self.history,
+                link_ids,
+                operation,
+            )
+        }
+    }
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn removes_links_as_one_undo_entry() {
+        let mut library = Library::new(&[&["a", "b"], &["c"]]);
+        let before = library.link_groups.clone();
+        let ids = vec![library.id(0, 0), library.id(1, 0), library.id(0, 0), 999];
+
+        let result = library.edit(ids, BulkOperation::Remove).unwrap();
+
+        assert_eq!(result.succeeded.len(), 2);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].id, 999);
+        assert_eq!(library.uris(0), vec!["b"]);
+        assert!(library.uris(1).is_empty());
+        assert_eq!(library.history.labels(), vec!["Remove links"]);
+
+        let entry = library
+            .history
+            .pop(&library.link_groups, &library.tags)
+            .unwrap();
+
+        assert!(entry.link_groups == before);
+    }
+
+    #[test]
+    fn links_in_locked_groups_fail_on_their_own() {
+        let mut library = Library::new(&[&["a"], &["b"]]);
+        library.link_groups[0].locked = true;
+
+        let ids = vec![library.id(0, 0), library.id(1, 0)];
+        let result = library.edit(ids.clone(), BulkOperation::Remove).unwrap();
+
+        assert_eq!(result.succeeded, vec![ids[1]]);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].id, ids[0]);
+        assert_eq!(library.uris(0), vec!["a"]);
+        assert!(library.uris(1).is_empty());
+    }
+
+    #[test]
+    fn moves_links_in_the_given_order() {
+        let mut library = Library::new(&[&["a", "b", "c"], &["d", "e"]]);
+        let ids = vec![library.id(0, 2), library.id(0, 0)];
+        let group_id = library.link_groups[1].id;
+
+        library
+            .edit(
+                ids,
+                BulkOperation::Move {
+                    group_id,
+                    position: Some(1),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(library.uris(0), vec!["b"]);
+        assert_eq!(library.uris(1), vec!["d", "c", "a", "e"]);
+    }
+
+    #[test]
+    fn locked_or_missing_targets_change_nothing() {
+        let mut library = Library::new(&[&["a"], &["b"]]);
+        library.link_groups[1].locked = true;
+
+        let before = library.link_groups.clone();
+        let ids = vec![library.id(0, 0)];
+        let group_id = library.link_groups[1].id;
+
+        for group_id in [group_id, 999] {
+            let moved = library.edit(
+                ids.clone(),
+                BulkOperation::Move {
+                    group_id,
+                    position: None,
+                },
+            );
+
+            assert!(moved.is_err());
+        }
+
+        assert!(library.link_groups == before);
+        assert!(library.history.labels().is_empty());
+    }
+
+    #[test]
+    fn copies_get_new_ids_and_no_snapshot() {
+        let mut library = Library::new(&[&["a"], &["b"]]);
+
+        library.link_groups[0].links[0].snapshot = Some(Snapshot {
+            path: "snapshot.html".to_string(),
+            size: 1,
+            created_at: 1,
+            self_contained: false,
+        });
+
+        let ids = vec![library.id(0, 0)];
+        let group_id = library.link_groups[1].id;
+
+        library
+            .edit(
+                ids,
+                BulkOperation::Copy {
+                    group_id,
+                    position: None,
+                },
+            )
+            .unwrap();
+
+        let original = &library.link_groups[0].links[0];
+        let copy = &library.link_groups[1].links[1];
+
+        assert_eq!(copy.uri, "a");
+        assert_ne!(copy.id, original.id);
+        assert!(original.snapshot.is_some());
+        assert!(copy.snapshot.is_none());
+    }
+
+    #[test]
+    fn adds_and_removes_resolved_tags() {
+        let mut library = Library::new(&[&["a", "b"]]);
+
+        library.tags.insert(
+            "javascript".to_string(),
+            TagInfo {
+                aliases: tags(&["js"]),
+                ..TagInfo::default()
+            },
+        );
+
+        let ids = vec![library.id(0, 0), library.id(0, 1)];
+
+        library
+            .edit(
+                ids.clone(),
+                BulkOperation::AddTags {
+                    tags: tags(&["js", " dev / rust "]),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            library.link_groups[0].links[1].tags,
+            tags(&["javascript", "dev/rust"])
+        );
+        assert!(library.tags.contains_key("dev/rust"));
+
+        library
+            .edit(
+                ids,
+                BulkOperation::RemoveTags {
+                    tags: tags(&["js"]),
+                },
+            )
+            .unwrap();
+
+        assert_eq!(library.link_groups[0].links[0].tags, tags(&["dev/rust"]));
+        assert_eq!(library.history.labels(), vec!["Remove tags", "Add tags"]);
+    }
+
+    #[test]
+    fn invalid_tags_fail_the_whole_call() {
+        let mut library = Library::new(&[&["a"]]);
+        let ids = vec![library.id(0, 0)];
+
+        let added = library.edit(
+            ids,
+            BulkOperation::AddTags {
+                tags: tags(&["ok", "not//ok"]),
+            },
+        );
+
+        assert!(added.is_err());
+        assert!(library.link_groups[0].links[0].tags.is_empty());
+        assert!(library.tags.is_empty());
+    }
+}
@@ -0,0 +1,20 @@
+use tauri::State;
+
+use crate::types::CanonicalizeContainer;
+
+#[tauri::command]
+pub fn set_tracking_params(
+    canonicalize: State<CanonicalizeContainer>,
+    params: Vec<String>,
+) -> Result<(), String> {
+    let lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let mut canonicalize_config = lock.borrow_mut();
+
+    canonicalize_config.tracking_params = params
+        .into_iter()
+        .map(|param| param.trim().to_lowercase())
+        .filter(|param| !param.is_empty())
+        .collect();
+
+    Ok(())
+}
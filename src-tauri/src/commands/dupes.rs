@@ -0,0 +1,217 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::{
+    tag_tree,
+    types::{CanonicalizeContainer, HistoryContainer, LinksContainer, TagsContainer},
+};
+
+#[derive(Deserialize)]
+pub enum KeepDupe {
+    First,
+    Last,
+    InLargestGroup,
+    InSmallestGroup,
+    WithMostTags,
+}
+
+#[derive(Serialize)]
+pub struct RemovedDupe {
+    id: usize,
+    group_id: usize,
+    uri: String,
+    title: String,
+    kept_id: usize,
+}
+
+#[derive(Serialize)]
+pub struct DupeResolutionSummary {
+    resolved_keys: usize,
+    removed: Vec<RemovedDupe>,
+}
+
+#[tauri::command]
+pub fn resolve_all_dupes(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    canonicalize: State<CanonicalizeContainer>,
+    history: State<HistoryContainer>,
+    keep: KeepDupe,
+) -> Result<DupeResolutionSummary, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let canonicalize_lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = canonicalize_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Resolve duplicates", &link_groups, &tags);
+
+    // Positions of every copy of a link, in group order
+    let mut copies = BTreeMap::<String, Vec<(usize, usize)>>::new();
+
+    for (group_pos, group) in link_groups.iter().enumerate() {
+        for (link_pos, link) in group.links.iter().enumerate() {
+            copies
+                .entry(canonicalize_config.canonicalize(&link.uri))
+                .or_default()
+                .push((group_pos, link_pos));
+        }
+    }
+
+    let mut resolved_keys = 0;
+    let mut removed = Vec::new();
+    let mut positions_to_remove = BTreeSet::<(usize, usize)>::new();
+
+    for positions in copies.into_values().filter(|positions| positions.len() > 1) {
+        let group_size = |(group_pos, _): &&(usize, usize)| link_groups[*group_pos].links.len();
+        let tag_count = |(group_pos, link_pos): &&(usize, usize)| {
+            link_groups[*group_pos].links[*link_pos].tags.len()
+        };
+
+        // Ties always go to the copy that comes first in group order
+        let survivor = *match keep {
+            KeepDupe::First => positions.first(),
+            KeepDupe::Last => positions.last(),
+            KeepDupe::InLargestGroup => positions
+                .iter()
+                .min_by_key(|position| Reverse(group_size(position))),
+            KeepDupe::InSmallestGroup => positions.iter().min_by_key(group_size),
+            KeepDupe::WithMostTags => positions
+                .iter()
+                .min_by_key(|position| Reverse(tag_count(position))),
+        }
+        .expect("Duplicates have at least two copies");
+
+        let kept_id = link_groups[survivor.0].links[survivor.1].id;
+        let removed_before = removed.len();
+        let mut merged_tags = link_groups[survivor.0].links[survivor.1].tags.clone();
+
+        // Copies in locked groups stay where they are
+        for &(group_pos, link_pos) in positions
+            .iter()
+            .filter(|position| **position != survivor && !link_groups[position.0].locked)
+        {
+            let group = &link_groups[group_pos];
+            let link = &group.links[link_pos];
+
+            tag_tree::union(&mut merged_tags, &link.tags);
+
+            removed.push(RemovedDupe {
+                id: link.id,
+                group_id: group.id,
+                uri: link.uri.to_owned(),
+                title: link.title.to_owned(),
+                kept_id,
+            });
+
+            positions_to_remove.insert((group_pos, link_pos));
+        }
+
+        // Nothing was resolved when every other copy is in a locked group
+        if removed.len() == removed_before {
+            continue;
+        }
+
+        let survivor = &mut link_groups[survivor.0].links[survivor.1];
+
+        if survivor.tags != merged_tags {
+            survivor.tags = merged_tags;
+            survivor.touch();
+        }
+
+        resolved_keys += 1;
+    }
+
+    // Removing back to front keeps the remaining positions valid
+    for (group_pos, link_pos) in positions_to_remove.into_iter().rev() {
+        link_groups[group_pos].links.remove(link_pos);
+        link_groups[group_pos].touch();
+    }
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(DupeResolutionSummary {
+        resolved_keys,
+        removed,
+    })
+}
+
+#[tauri::command]
+pub fn merge_links(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    keep_id: usize,
+    ids: Vec<usize>,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Merge links", &link_groups, &tags);
+
+    let mut merged_tags = link_groups
+        .iter()
+        .flat_map(|group| &group.links)
+        .find(|link| link.id == keep_id)
+        .map(|link| link.tags.clone())
+        .ok_or(format!("Link {} wasn't found", keep_id))?;
+
+    let ids = ids
+        .into_iter()
+        .filter(|id| *id != keep_id)
+        .collect::<BTreeSet<_>>();
+
+    let found = link_groups
+        .iter()
+        .flat_map(|group| &group.links)
+        .filter(|link| ids.contains(&link.id))
+        .inspect(|link| tag_tree::union(&mut merged_tags, &link.tags))
+        .count();
+
+    if found != ids.len() {
+        return Err("Some of the links to merge weren't found".to_string());
+    }
+
+    for group in link_groups
+        .iter()
+        .filter(|group| group.links.iter().any(|link| ids.contains(&link.id)))
+    {
+        group.check_unlocked()?;
+    }
+
+    for group in link_groups.iter_mut() {
+        let before = group.links.len();
+
+        group.links.retain(|link| !ids.contains(&link.id));
+
+        if group.links.len() != before {
+            group.touch();
+        }
+
+        if let Some(link) = group.links.iter_mut().find(|link| link.id == keep_id) {
+            link.tags = merged_tags.clone();
+            link.touch();
+        }
+    }
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    folders::{self, Folder},
+    html,
+    types::{Link, LinkGroups},
+};
+
+// Folders become bookmark folders, and each group a folder of its own inside them
+pub enum Node<'a> {
+    Folder {
+        name: String,
+        created_at: Option<u64>,
+        updated_at: Option<u64>,
+        children: Vec<Node<'a>>,
+    },
+    Link(&'a Link),
+}
+
+// Subfolders come before the groups sharing their parent
+pub fn build_tree<'a>(
+    folders: &[Folder],
+    link_groups: &'a LinkGroups,
+    parent_id: Option<usize>,
+) -> Vec<Node<'a>> {
+    let mut nodes = folders::children(folders, parent_id)
+        .map(|folder| Node::Folder {
+            name: folder.name.to_owned(),
+            created_at: None,
+            updated_at: None,
+            children: build_tree(folders, link_groups, Some(folder.id)),
+        })
+        .collect::<Vec<_>>();
+
+    nodes.extend(
+        link_groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| folders::existing(folders, group.folder_id) == parent_id)
+            .map(|(position, group)| Node::Folder {
+                name: group
+                    .title
+                    .to_owned()
+                    .unwrap_or_else(|| format!("Group {}", position + 1)),
+                created_at: group.created_at,
+                updated_at: group.updated_at,
+                children: group.links.iter().map(Node::Link).collect(),
+            }),
+    );
+
+    nodes
+}
+
+fn write_netscape_nodes(
+    writer: &mut impl Write,
+    nodes: &[Node],
+    depth: usize,
+) -> std::io::Result<()> {
+    let indent = "    ".repeat(depth);
+
+    // Netscape files count time in seconds
+    let date = |name: &str, time: Option<u64>| {
+        time.map_or(String::new(), |time| {
+            format!(" {}=\"{}\"", name, time / 1000)
+        })
+    };
+
+    for node in nodes {
+        match node {
+            Node::Folder {
+                name,
+                created_at,
+                updated_at,
+                children,
+            } => {
+                writeln!(
+                    writer,
+                    "{}<DT><H3{}{}>{}</H3>",
+                    indent,
+                    date("ADD_DATE", *created_at),
+                    date("LAST_MODIFIED", *updated_at),
+                    html::escape_text(name)
+                )?;
+                writeln!(writer, "{}<DL><p>", indent)?;
+                write_netscape_nodes(writer, children, depth + 1)?;
+                writeln!(writer, "{}</DL><p>", indent)?;
+            }
+            Node::Link(link) => {
+                let tags = match link.tags.is_empty() {
+                    true => String::new(),
+                    false => format!(" TAGS=\"{}\"", html::escape_attribute(&link.tags.join(","))),
+                };
+
+                writeln!(
+                    writer,
+                    "{}<DT><A HREF=\"{}\"{}{}{}{}>{}</A>",
+                    indent,
+                    html::escape_attribute(&link.uri),
+                    date("ADD_DATE", link.created_at),
+                    date("LAST_MODIFIED", link.updated_at),
+                    date("LAST_VISIT", link.last_opened_at),
+                    tags,
+                    html::escape_text(&link.title)
+                )?;
+
+                if let Some(ref note) = link.note {
+                    writeln!(writer, "{}<DD>{}", indent, html::escape_text(note))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn write_netscape(path: String, nodes: &[Node]) -> Result<(), String> {
+    let mut writer = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+
+    write!(
+        writer,
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n\
+         <DL><p>\n"
+    )
+    .and_then(|_| write_netscape_nodes(&mut writer, nodes, 1))
+    .and_then(|_| writeln!(writer, "</DL><p>"))
+    .and_then(|_| writer.flush())
+    .map_err(|e| e.to_string())
+}
+
+// Microseconds since 1601, which is how Chromium stores dates
+fn chromium_time(time: Option<u64>) -> String {
+    const UNIX_EPOCH_OFFSET_MICROS: u64 = 11_644_473_600_000_000;
+
+    time.map_or(0, |time| time * 1000 + UNIX_EPOCH_OFFSET_MICROS)
+        .to_string()
+}
+
+fn chromium_nodes(nodes: &[Node], next_id: &mut usize) -> Vec<Value> {
+    nodes
+        .iter()
+        .map(|node| {
+            *next_id += 1;
+            let id = next_id.to_string();
+
+            match node {
+                Node::Folder {
+                    name,
+                    created_at,
+                    updated_at,
+                    children,
+                } => json!({
+                    "children": chromium_nodes(children, next_id),
+                    "date_added": chromium_time(*created_at),
+                    "date_modified": chromium_time(*updated_at),
+                    "id": id,
+                    "name": name,
+                    "type": "folder",
+                }),
+                Node::Link(link) => json!({
+                    "date_added": chromium_time(link.created_at),
+                    "date_last_used": chromium_time(link.last_opened_at),
+                    "id": id,
+                    "name": link.title,
+                    "type": "url",
+                    "url": link.uri,
+                }),
+            }
+        })
+        .collect()
+}
+
+pub fn write_chromium(path: String, nodes: &[Node]) -> Result<(), String> {
+    // Ids 1 to 3 belong to the root folders
+    let mut next_id = 3;
+
+    let root = |id: &str, name: &str, children: Vec<Value>| {
+        json!({
+            "children": children,
+            "date_added": "0",
+            "date_modified": "0",
+            "id": id,
+            "name": name,
+            "type": "folder",
+        })
+    };
+
+    let bookmarks = json!({
+        "roots": {
+            "bookmark_bar": root("1", "Bookmarks bar", chromium_nodes(nodes, &mut next_id)),
+            "other": root("2", "Other bookmarks", Vec::new()),
+            "synced": root("3", "Mobile bookmarks", Vec::new()),
+        },
+        "version": 1,
+    });
+
+    let writer = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+
+    serde_json::to_writer_pretty(writer, &bookmarks).map_err(|e| e.to_string())
+}
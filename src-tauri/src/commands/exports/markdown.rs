@@ -0,0 +1,83 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use super::bookmarks::Node;
+
+// Backslash escapes anything that would otherwise be read as formatting
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+// Link destinations end at a space or an unmatched parenthesis
+fn escape_uri(uri: &str) -> String {
+    uri.replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+fn write_nodes(writer: &mut impl Write, nodes: &[Node], depth: usize) -> std::io::Result<()> {
+    for node in nodes {
+        match node {
+            Node::Folder { name, children, .. } => {
+                // Markdown has no headings past the sixth level
+                writeln!(writer, "{} {}\n", "#".repeat(depth.min(6)), escape(name))?;
+                write_nodes(writer, children, depth + 1)?;
+            }
+            Node::Link(link) => {
+                write!(
+                    writer,
+                    "- [{}]({})",
+                    escape(&link.title),
+                    escape_uri(&link.uri)
+                )?;
+
+                for tag in link.tags.iter() {
+                    write!(writer, " `{}`", tag.replace('`', "'"))?;
+                }
+
+                writeln!(writer)?;
+
+                // Notes are already Markdown, so they're only indented to stay
+                // inside the list item
+                if let Some(ref note) = link.note {
+                    writeln!(writer)?;
+
+                    for line in note.lines() {
+                        match line.is_empty() {
+                            true => writeln!(writer)?,
+                            false => writeln!(writer, "  {}", line)?,
+                        }
+                    }
+
+                    writeln!(writer)?;
+                }
+            }
+        }
+    }
+
+    if nodes.iter().any(|node| matches!(node, Node::Link(_))) {
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+pub fn write_markdown(path: String, nodes: &[Node]) -> Result<(), String> {
+    let mut writer = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+
+    write_nodes(&mut writer, nodes, 1)
+        .and_then(|_| writer.flush())
+        .map_err(|e| e.to_string())
+}
@@ -5,12 +5,24 @@ use std::{
 
 use tauri::State;
 
-use crate::types::{LinksContainer, SaveData, TagsContainer};
+use crate::{
+    folders,
+    types::{
+        CanonicalizeContainer, FoldersContainer, LinksContainer, SaveData, StorableTag,
+        TagRulesContainer, TagsContainer,
+    },
+};
+
+mod bookmarks;
+mod markdown;
 
 #[tauri::command]
 pub fn save_data(
     links: State<LinksContainer>,
     tags: State<TagsContainer>,
+    canonicalize: State<CanonicalizeContainer>,
+    tag_rules: State<TagRulesContainer>,
+    folders: State<FoldersContainer>,
     path: String,
 ) -> Result<(), String> {
     let mut links_lock = links.lock().map_err(|e| e.to_string())?;
@@ -19,15 +31,38 @@ pub fn save_data(
     let mut tags_lock = tags.lock().map_err(|e| e.to_string())?;
     let tags = tags_lock.get_mut();
 
-    let save_data = SaveData {
-        tags: tags.iter().map(|tag| tag.to_owned()).collect(),
+    let canonicalize_lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = canonicalize_lock.borrow();
+
+    let rules_lock = tag_rules.lock().map_err(|e| e.to_string())?;
+    let tag_rules = rules_lock.borrow();
+
+    let folders_lock = folders.lock().map_err(|e| e.to_string())?;
+    let folders = folders_lock.borrow();
+
+    let mut save_data = SaveData {
+        tags: tags
+            .iter()
+            .map(|(name, info)| StorableTag {
+                name: name.to_owned(),
+                info: info.to_owned(),
+            })
+            .collect(),
         link_groups: link_groups
             .clone()
             .into_iter()
             .map(|group| group.into())
             .collect(),
+        canonicalize: Some(canonicalize_config.clone()),
+        tag_rules: tag_rules
+            .iter()
+            .map(|rule| rule.to_owned().into())
+            .collect(),
+        folders: folders::to_storable(&folders),
     };
 
+    folders::store_group_folders(&folders, link_groups, &mut save_data);
+
     {
         let writer = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
 
@@ -45,6 +80,8 @@ pub fn export_for_onetab(links: State<LinksContainer>, path: String) -> Result<(
     {
         let mut writer = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
 
+        // Only links are written, as OneTab has no place for group details. The JSON
+        // export keeps those
         link_groups.iter().for_each(|group| {
             group.links.iter().for_each(|link| {
                 writeln!(&mut writer, "{} | {}", link.uri, link.title)
@@ -57,3 +94,48 @@ pub fn export_for_onetab(links: State<LinksContainer>, path: String) -> Result<(
 
     Ok(())
 }
+
+#[tauri::command]
+pub fn export_as_netscape(
+    links: State<LinksContainer>,
+    folders: State<FoldersContainer>,
+    path: String,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let folders_lock = folders.lock().map_err(|e| e.to_string())?;
+    let folders = folders_lock.borrow();
+
+    bookmarks::write_netscape(path, &bookmarks::build_tree(&folders, &link_groups, None))
+}
+
+#[tauri::command]
+pub fn export_as_chromium(
+    links: State<LinksContainer>,
+    folders: State<FoldersContainer>,
+    path: String,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let folders_lock = folders.lock().map_err(|e| e.to_string())?;
+    let folders = folders_lock.borrow();
+
+    bookmarks::write_chromium(path, &bookmarks::build_tree(&folders, &link_groups, None))
+}
+
+#[tauri::command]
+pub fn export_as_markdown(
+    links: State<LinksContainer>,
+    folders: State<FoldersContainer>,
+    path: String,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let folders_lock = folders.lock().map_err(|e| e.to_string())?;
+    let folders = folders_lock.borrow();
+
+    markdown::write_markdown(path, &bookmarks::build_tree(&folders, &link_groups, None))
+}
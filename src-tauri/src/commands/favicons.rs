@@ -0,0 +1,72 @@
+use std::{collections::BTreeMap, fs};
+
+use tauri::{AppHandle, State};
+use url::Url;
+
+use crate::{
+    favicons, jobs,
+    net::{self, FetchOptions, HostRateLimiter},
+    types::{JobsContainer, LinksContainer},
+};
+
+#[tauri::command]
+pub fn fetch_favicons(
+    app: AppHandle,
+    links: State<LinksContainer>,
+    jobs: State<JobsContainer>,
+    force: Option<bool>,
+    options: Option<FetchOptions>,
+) -> Result<usize, String> {
+    let options = options.unwrap_or_default();
+    let dir = favicons::cache_dir(&app)?;
+
+    // One request per host, using the scheme of the first link seen for it
+    let hosts = {
+        let lock = links.lock().map_err(|e| e.to_string())?;
+        let link_groups = lock.borrow();
+
+        let mut hosts = BTreeMap::<String, String>::new();
+
+        for url in link_groups
+            .iter()
+            .flat_map(|group| &group.links)
+            .filter_map(|link| Url::parse(&link.uri).ok())
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+        {
+            if let Some(host) = url.host_str().map(|host| host.to_ascii_lowercase()) {
+                hosts.entry(host).or_insert(url.scheme().to_string());
+            }
+        }
+
+        hosts
+            .into_iter()
+            .filter(|(host, _)| favicons::is_valid_host(host))
+            .filter(|(host, _)| force.unwrap_or(false) || !favicons::is_fresh(&dir, host))
+            .collect::<Vec<_>>()
+    };
+
+    let agent = net::build_agent(options.timeout(), favicons::MAX_REDIRECTS);
+    let rate_limiter = HostRateLimiter::new(options.host_interval());
+
+    jobs::spawn(
+        app,
+        &jobs,
+        favicons::JOB_NAME,
+        hosts,
+        options.concurrency,
+        move |_, (host, scheme)| {
+            rate_limiter.wait(&format!("{}://{}/", scheme, host));
+
+            favicons::fetch_and_store(&agent, &dir, &host, &scheme)
+        },
+    )
+}
+
+#[tauri::command]
+pub fn clear_favicon_cache(app: AppHandle) -> Result<(), String> {
+    let dir = favicons::cache_dir(&app)?;
+
+    fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
@@ -0,0 +1,236 @@
+use tauri::State;
+
+use crate::{
+    folders::{self, Folder},
+    types::{
+        get_new_id, sort_pinned_first, FoldersContainer, HistoryContainer, LinksContainer,
+        TagsContainer,
+    },
+};
+
+#[tauri::command]
+pub fn add_folder(
+    folders: State<FoldersContainer>,
+    name: String,
+    parent_id: Option<usize>,
+) -> Result<usize, String> {
+    let name = folders::validate_name(&name)?;
+
+    let lock = folders.lock().map_err(|e| e.to_string())?;
+    let mut folders = lock.borrow_mut();
+
+    if let Some(parent_id) = parent_id {
+        folders::find(&folders, parent_id)?;
+    }
+
+    let id = get_new_id();
+
+    folders.push(Folder {
+        id,
+        name,
+        parent_id,
+    });
+
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn rename_folder(
+    folders: State<FoldersContainer>,
+    id: usize,
+    name: String,
+) -> Result<(), String> {
+    let name = folders::validate_name(&name)?;
+
+    let lock = folders.lock().map_err(|e| e.to_string())?;
+    let mut folders = lock.borrow_mut();
+
+    let folder = folders
+        .iter_mut()
+        .find(|folder| folder.id == id)
+        .ok_or(format!("Folder {} wasn't found", id))?;
+
+    folder.name = name;
+
+    Ok(())
+}
+
+// Moves the folder under `parent_id`, or to the top level, at `position` among the
+// folders already there
+#[tauri::command]
+pub fn move_folder(
+    folders: State<FoldersContainer>,
+    id: usize,
+    parent_id: Option<usize>,
+    position: usize,
+) -> Result<(), String> {
+    let lock = folders.lock().map_err(|e| e.to_string())?;
+    let mut folders = lock.borrow_mut();
+
+    let folder_pos = folders
+        .iter()
+        .position(|folder| folder.id == id)
+        .ok_or(format!("Folder {} wasn't found", id))?;
+
+    if let Some(parent_id) = parent_id {
+        folders::find(&folders, parent_id)?;
+
+        if folders::is_within(&folders, parent_id, id) {
+            return Err("A folder can't be moved inside itself".to_string());
+        }
+    }
+
+    let mut folder = folders.remove(folder_pos);
+    folder.parent_id = parent_id;
+
+    // Placed before the sibling currently at `position`, or after the last one
+    let siblings = folders
+        .iter()
+        .enumerate()
+        .filter(|(_, sibling)| sibling.parent_id == parent_id)
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    let index = match siblings.get(position) {
+        Some(index) => *index,
+        None => siblings.last().map_or(folders.len(), |index| index + 1),
+    };
+
+    folders.insert(index, folder);
+
+    Ok(())
+}
+
+// Removes the folder and everything inside it with `remove_groups`, otherwise its
+// groups and folders are moved up into its parent
+#[tauri::command]
+pub fn remove_folder(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    folders: State<FoldersContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+    remove_groups: bool,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let folders_lock = folders.lock().map_err(|e| e.to_string())?;
+    let mut folders = folders_lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Remove folder", &link_groups, &tags);
+
+    let parent_id = folders::find(&folders, id)?.parent_id;
+
+    if remove_groups {
+        let within = |folder_id: Option<usize>| {
+            folder_id.is_some_and(|folder_id| folders::is_within(&folders, folder_id, id))
+        };
+
+        for group in link_groups.iter().filter(|group| within(group.folder_id)) {
+            group.check_unlocked()?;
+        }
+
+        link_groups.retain(|group| !within(group.folder_id));
+
+        let removed = folders
+            .iter()
+            .filter(|folder| folders::is_within(&folders, folder.id, id))
+            .map(|folder| folder.id)
+            .collect::<Vec<_>>();
+
+        folders.retain(|folder| !removed.contains(&folder.id));
+    } else {
+        for group in link_groups
+            .iter_mut()
+            .filter(|group| group.folder_id == Some(id))
+        {
+            group.folder_id = parent_id;
+            group.touch();
+        }
+
+        for folder in folders
+            .iter_mut()
+            .filter(|folder| folder.parent_id == Some(id))
+        {
+            folder.parent_id = parent_id;
+        }
+
+        folders.retain(|folder| folder.id != id);
+    }
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
+
+// Moves the group into the folder, or out to the top level, at `position` among the
+// groups already there
+#[tauri::command]
+pub fn move_group_to_folder(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    folders: State<FoldersContainer>,
+    history: State<HistoryContainer>,
+    group_id: usize,
+    folder_id: Option<usize>,
+    position: usize,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let folders_lock = folders.lock().map_err(|e| e.to_string())?;
+    let folders = folders_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Move group", &link_groups, &tags);
+
+    if let Some(folder_id) = folder_id {
+        folders::find(&folders, folder_id)?;
+    }
+
+    let group_pos = link_groups
+        .iter()
+        .position(|group| group.id == group_id)
+        .ok_or("Group matching provided id not found")?;
+
+    link_groups[group_pos].check_unlocked()?;
+
+    let mut group = link_groups
+        .remove(group_pos)
+        .expect("Matched group not found");
+
+    group.folder_id = folder_id;
+    group.touch();
+
+    let siblings = link_groups
+        .iter()
+        .enumerate()
+        .filter(|(_, sibling)| sibling.folder_id == folder_id)
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    let index = match siblings.get(position) {
+        Some(index) => *index,
+        None => siblings.last().map_or(link_groups.len(), |index| index + 1),
+    };
+
+    link_groups.insert(index, group);
+
+    sort_pinned_first(&mut link_groups);
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
@@ -1,43 +1,90 @@
-use crate::types::{LinkGroup, LinksContainer};
+use crate::types::{
+    is_valid_color, sort_pinned_first, HistoryContainer, LinkGroup, LinksContainer, TagsContainer,
+};
 use std::{borrow::BorrowMut, collections::VecDeque};
 use tauri::State;
 
 #[tauri::command]
-pub fn add_group(links: State<LinksContainer>) -> Result<(), String> {
+pub fn add_group(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+) -> Result<(), String> {
     let mut lock = links.lock().map_err(|e| e.to_string())?;
     let link_groups = lock.borrow_mut();
 
-    link_groups
-        .get_mut()
-        .push_front(LinkGroup::new(VecDeque::new()));
+    let link_groups = link_groups.get_mut();
+
+    let mut tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.get_mut();
+
+    let mut history_lock = history.lock().map_err(|e| e.to_string())?;
+    let history = history_lock.get_mut();
+
+    let recording = history.record("Add group", link_groups, tags);
+
+    link_groups.push_front(LinkGroup::new(VecDeque::new()));
+
+    sort_pinned_first(link_groups);
+
+    history.finish(recording, link_groups, tags);
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn remove_group(links: State<LinksContainer>, id: usize) -> Result<(), String> {
+pub fn remove_group(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+) -> Result<(), String> {
     let mut lock = links.lock().map_err(|e| e.to_string())?;
     let link_groups = lock.borrow_mut().get_mut();
 
+    let mut tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.get_mut();
+
+    let mut history_lock = history.lock().map_err(|e| e.to_string())?;
+    let history = history_lock.get_mut();
+
+    let recording = history.record("Remove group", link_groups, tags);
+
     let position = link_groups
         .iter()
         .position(|link_group| link_group.id == id)
         .ok_or(String::from("Group Not Found"))?;
 
+    link_groups[position].check_unlocked()?;
+
     link_groups.remove(position);
 
+    history.finish(recording, link_groups, tags);
+
     Ok(())
 }
 
 #[tauri::command]
-pub fn remove_empty_groups(links: State<LinksContainer>) -> Result<(), String> {
+pub fn remove_empty_groups(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+) -> Result<(), String> {
     let mut lock = links.lock().map_err(|e| e.to_string())?;
     let link_groups = lock.borrow_mut().get_mut();
 
+    let mut tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.get_mut();
+
+    let mut history_lock = history.lock().map_err(|e| e.to_string())?;
+    let history = history_lock.get_mut();
+
+    let recording = history.record("Remove empty groups", link_groups, tags);
+
     let groups_to_remove = link_groups
         .iter()
         .enumerate()
-        .filter(|(_, group)| group.links.is_empty())
+        .filter(|(_, group)| group.links.is_empty() && !group.locked)
         .map(|(i, _)| i)
         .rev()
         .collect::<Vec<_>>();
@@ -46,5 +93,101 @@ pub fn remove_empty_groups(links: State<LinksContainer>) -> Result<(), String> {
         link_groups.remove(group);
     }
 
+    history.finish(recording, link_groups, tags);
+
+    Ok(())
+}
+
+// Blank fields are cleared rather than stored
+#[tauri::command]
+pub fn update_group(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+    title: Option<String>,
+    description: Option<String>,
+    color: Option<String>,
+) -> Result<(), String> {
+    let non_empty = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let color = non_empty(color);
+
+    if let Some(ref color) = color {
+        if !is_valid_color(color) {
+            return Err(format!("Invalid color: '{}'", color));
+        }
+    }
+
+    let mut lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow_mut().get_mut();
+
+    let mut tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.get_mut();
+
+    let mut history_lock = history.lock().map_err(|e| e.to_string())?;
+    let history = history_lock.get_mut();
+
+    let recording = history.record("Edit group", link_groups, tags);
+
+    let group = link_groups
+        .iter_mut()
+        .find(|link_group| link_group.id == id)
+        .ok_or(String::from("Group Not Found"))?;
+
+    group.title = non_empty(title);
+    group.description = non_empty(description);
+    group.color = color;
+    group.touch();
+
+    history.finish(recording, link_groups, tags);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_group_flags(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+    pinned: Option<bool>,
+    starred: Option<bool>,
+    locked: Option<bool>,
+) -> Result<(), String> {
+    let mut lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow_mut().get_mut();
+
+    let mut tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.get_mut();
+
+    let mut history_lock = history.lock().map_err(|e| e.to_string())?;
+    let history = history_lock.get_mut();
+
+    let recording = history.record("Change group flags", link_groups, tags);
+
+    let group = link_groups
+        .iter_mut()
+        .find(|link_group| link_group.id == id)
+        .ok_or(String::from("Group Not Found"))?;
+
+    // Pinning moves the group, which isn't allowed while it stays locked
+    if pinned.is_some_and(|pinned| pinned != group.pinned) && locked.unwrap_or(group.locked) {
+        group.check_unlocked()?;
+    }
+
+    group.pinned = pinned.unwrap_or(group.pinned);
+    group.starred = starred.unwrap_or(group.starred);
+    group.locked = locked.unwrap_or(group.locked);
+    group.touch();
+
+    sort_pinned_first(link_groups);
+
+    history.finish(recording, link_groups, tags);
+
     Ok(())
 }
@@ -0,0 +1,125 @@
+use tauri::{AppHandle, State};
+
+use crate::{
+    health, jobs,
+    net::{self, FetchOptions, HostRateLimiter},
+    tag_tree,
+    types::{HistoryContainer, JobsContainer, LinksContainer, TagsContainer},
+};
+
+#[tauri::command]
+pub fn check_links(
+    app: AppHandle,
+    links: State<LinksContainer>,
+    jobs: State<JobsContainer>,
+    ids: Option<Vec<usize>>,
+    options: Option<FetchOptions>,
+) -> Result<usize, String> {
+    let options = options.unwrap_or_default();
+
+    let targets = {
+        let lock = links.lock().map_err(|e| e.to_string())?;
+        let link_groups = lock.borrow();
+
+        link_groups
+            .iter()
+            .flat_map(|group| &group.links)
+            .filter(|link| ids.as_ref().is_none_or(|ids| ids.contains(&link.id)))
+            .map(|link| (link.id, link.uri.to_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    let agent = net::build_agent(options.timeout(), health::MAX_REDIRECTS);
+    let rate_limiter = HostRateLimiter::new(options.host_interval());
+
+    jobs::spawn(
+        app,
+        &jobs,
+        health::JOB_NAME,
+        targets,
+        options.concurrency,
+        move |app, (link_id, uri)| {
+            rate_limiter.wait(&uri);
+
+            health::record_health(app, link_id, health::check_link(&agent, &uri))
+        },
+    )
+}
+
+#[tauri::command]
+pub fn remove_dead_links(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+) -> Result<usize, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Remove dead links", &link_groups, &tags);
+
+    let mut removed = 0;
+
+    for group in link_groups.iter_mut().filter(|group| !group.locked) {
+        let before = group.links.len();
+
+        group
+            .links
+            .retain(|link| !link.health.as_ref().is_some_and(|health| health.is_dead()));
+
+        if group.links.len() != before {
+            group.touch();
+            removed += before - group.links.len();
+        }
+    }
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(removed)
+}
+
+#[tauri::command]
+pub fn tag_dead_links(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    tag_name: String,
+) -> Result<usize, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Tag dead links", &link_groups, &tags);
+
+    let tag_name = tag_tree::resolve(&tags, &tag_tree::normalize(&tag_name)?);
+
+    let mut tagged = 0;
+
+    for link in link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+        .filter(|link| link.health.as_ref().is_some_and(|health| health.is_dead()))
+    {
+        if !link.tags.contains(&tag_name) {
+            link.tags.push(tag_name.to_owned());
+            link.touch();
+            tagged += 1;
+        }
+    }
+
+    tags.entry(tag_name).or_default();
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(tagged)
+}
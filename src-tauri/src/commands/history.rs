@@ -0,0 +1,48 @@
+use tauri::State;
+
+use crate::types::{FoldersContainer, HistoryContainer, LinkGroups, LinksContainer, TagsContainer};
+
+// Puts back the library as it was before the last recorded operation, returning
+// that operation's label
+#[tauri::command]
+pub fn undo(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    folders: State<FoldersContainer>,
+    history: State<HistoryContainer>,
+) -> Result<String, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let folders_lock = folders.lock().map_err(|e| e.to_string())?;
+    let folders = folders_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let entry = history.pop(&link_groups, &tags)?;
+
+    *link_groups = entry.link_groups;
+    *tags = entry.tags;
+
+    // Folders aren't part of the history, so groups filed in one that's been removed
+    // since go back to the top level, in every copy so that undoing can go on
+    let unfile_removed = |link_groups: &mut LinkGroups| {
+        for group in link_groups.iter_mut() {
+            if group
+                .folder_id
+                .is_some_and(|folder_id| !folders.iter().any(|folder| folder.id == folder_id))
+            {
+                group.folder_id = None;
+            }
+        }
+    };
+
+    unfile_removed(&mut link_groups);
+    history.apply_to_all(unfile_removed);
+
+    Ok(entry.label)
+}
@@ -0,0 +1,283 @@
+use std::collections::VecDeque;
+
+use serde_json::Value;
+
+use crate::{
+    html::{self, Attributes},
+    tag_tree,
+    types::{Link, LinkGroup},
+};
+
+// Every bookmark folder holding links becomes a group, in the order the folders
+// start in. Only web links are kept, as bookmark files also hold scripts and
+// browser-specific places
+struct BookmarkFolder {
+    title: Option<String>,
+    created_at: Option<u64>,
+    updated_at: Option<u64>,
+    links: VecDeque<Link>,
+}
+
+impl BookmarkFolder {
+    fn into_group(self) -> Option<LinkGroup> {
+        if self.links.is_empty() {
+            return None;
+        }
+
+        let mut group = LinkGroup::new(self.links);
+
+        group.title = self.title;
+        group.created_at = self.created_at.or(group.created_at);
+        group.updated_at = self.updated_at;
+
+        Some(group)
+    }
+}
+
+fn is_web_link(uri: &str) -> bool {
+    uri.starts_with("https://") || uri.starts_with("http://")
+}
+
+fn new_link(uri: String, title: &str, tags: Vec<String>) -> Link {
+    // Missing titles fall back to the URI, like in the OneTab import
+    let title = match title.trim() {
+        "" => uri.to_owned(),
+        title => title.to_string(),
+    };
+
+    Link::new(uri, title, tags)
+}
+
+// Netscape files count time in seconds
+fn netscape_time(attributes: &Attributes, name: &str) -> Option<u64> {
+    attributes
+        .get(name)
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|seconds| *seconds > 0)
+        .map(|seconds| seconds * 1000)
+}
+
+// The text from `from` up to the next tag, with entities decoded
+fn text_until_tag(file: &str, from: usize) -> String {
+    let end = file[from..].find('<').map_or(file.len(), |end| from + end);
+
+    html::decode_entities(file[from..end].trim())
+}
+
+pub fn netscape_import(
+    file_as_string: String,
+    link_groups: &mut VecDeque<LinkGroup>,
+) -> Result<(), String> {
+    // ASCII lowercasing keeps byte offsets the same as the original
+    let lowercase = file_as_string.to_ascii_lowercase();
+
+    if !lowercase.contains("<dl") {
+        return Err("Corrupted file or invalid format".to_string());
+    }
+
+    // Open folders along with the position they started in
+    let mut open = Vec::<(usize, BookmarkFolder)>::new();
+    let mut closed = Vec::<(usize, BookmarkFolder)>::new();
+    let mut heading = None::<BookmarkFolder>;
+    // Descriptions can follow folder headings too, which aren't kept
+    let mut after_link = false;
+    let mut started = 0;
+    let mut from = 0;
+
+    while let Some(offset) = lowercase[from..].find('<') {
+        let start = from + offset;
+
+        let Some(end) = lowercase[start..].find('>').map(|end| start + end) else {
+            break;
+        };
+
+        let name = lowercase[start + 1..end]
+            .split_ascii_whitespace()
+            .next()
+            .unwrap_or_default();
+        let attributes = || html::parse_attributes(&file_as_string[start + 1 + name.len()..end]);
+
+        from = end + 1;
+
+        match name.trim_end_matches('/') {
+            "h3" => {
+                let attributes = attributes();
+                let title = text_until_tag(&file_as_string, from);
+
+                after_link = false;
+
+                heading = Some(BookmarkFolder {
+                    title: Some(title).filter(|title| !title.is_empty()),
+                    created_at: netscape_time(&attributes, "add_date"),
+                    updated_at: netscape_time(&attributes, "last_modified"),
+                    links: VecDeque::new(),
+                });
+            }
+            "dl" => {
+                let folder = heading.take().unwrap_or(BookmarkFolder {
+                    title: None,
+                    created_at: None,
+                    updated_at: None,
+                    links: VecDeque::new(),
+                });
+
+                open.push((started, folder));
+                started += 1;
+                after_link = false;
+            }
+            "/dl" => {
+                closed.extend(open.pop());
+                after_link = false;
+            }
+            "a" => {
+                let attributes = attributes();
+                let title = text_until_tag(&file_as_string, from);
+
+                // Skipped links take their description with them
+                after_link = false;
+
+                let Some(uri) = attributes
+                    .get("href")
+                    .map(|uri| uri.trim().to_string())
+                    .filter(|uri| is_web_link(uri))
+                else {
+                    continue;
+                };
+
+                let tags = attributes
+                    .get("tags")
+                    .map(|tags| {
+                        tags.split(',')
+                            .filter_map(|tag| tag_tree::normalize(tag).ok())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let mut link = new_link(uri, &title, tags);
+
+                link.created_at = netscape_time(&attributes, "add_date").or(link.created_at);
+                link.updated_at = netscape_time(&attributes, "last_modified");
+                link.last_opened_at = netscape_time(&attributes, "last_visit");
+
+                if let Some((_, folder)) = open.last_mut() {
+                    folder.links.push_back(link);
+                    after_link = true;
+                }
+            }
+            // Descriptions follow the link they belong to
+            "dd" => {
+                let note = text_until_tag(&file_as_string, from);
+
+                let link = open
+                    .last_mut()
+                    .and_then(|(_, folder)| folder.links.back_mut());
+
+                if let Some(link) = link.filter(|_| after_link && !note.is_empty()) {
+                    link.note = Some(note);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Folders left open by a truncated file still keep their links
+    closed.extend(open.into_iter().rev());
+    closed.sort_by_key(|(started, _)| *started);
+
+    link_groups.extend(
+        closed
+            .into_iter()
+            .filter_map(|(_, folder)| folder.into_group()),
+    );
+
+    Ok(())
+}
+
+// Microseconds since 1601, which is how Chromium stores dates
+fn chromium_time(node: &Value, name: &str) -> Option<u64> {
+    const UNIX_EPOCH_OFFSET_MICROS: u64 = 11_644_473_600_000_000;
+
+    node.get(name)
+        .and_then(Value::as_str)
+        .and_then(|value| value.parse::<u64>().ok())
+        .and_then(|micros| micros.checked_sub(UNIX_EPOCH_OFFSET_MICROS))
+        .map(|micros| micros / 1000)
+}
+
+pub fn is_chromium(file_as_string: &str) -> bool {
+    serde_json::from_str::<Value>(file_as_string)
+        .is_ok_and(|value| value.get("roots").is_some_and(Value::is_object))
+}
+
+fn chromium_folder(node: &Value, link_groups: &mut VecDeque<LinkGroup>) {
+    let mut folder = BookmarkFolder {
+        title: node
+            .get("name")
+            .and_then(Value::as_str)
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty()),
+        created_at: chromium_time(node, "date_added"),
+        updated_at: chromium_time(node, "date_modified"),
+        links: VecDeque::new(),
+    };
+
+    // The folder's own group comes before those of its subfolders
+    let position = link_groups.len();
+
+    for child in node
+        .get("children")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        match child.get("type").and_then(Value::as_str) {
+            Some("folder") => chromium_folder(child, link_groups),
+            Some("url") => {
+                let Some(uri) = child
+                    .get("url")
+                    .and_then(Value::as_str)
+                    .map(|uri| uri.trim().to_string())
+                    .filter(|uri| is_web_link(uri))
+                else {
+                    continue;
+                };
+
+                let title = child
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+
+                let mut link = new_link(uri, title, Vec::new());
+
+                link.created_at = chromium_time(child, "date_added").or(link.created_at);
+                link.last_opened_at = chromium_time(child, "date_last_used");
+
+                folder.links.push_back(link);
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(group) = folder.into_group() {
+        link_groups.insert(position, group);
+    }
+}
+
+pub fn chromium_import(
+    file_as_string: String,
+    link_groups: &mut VecDeque<LinkGroup>,
+) -> Result<(), String> {
+    let bookmarks = serde_json::from_str::<Value>(&file_as_string)
+        .or(Err("Corrupted file or invalid format"))?;
+
+    let roots = bookmarks
+        .get("roots")
+        .and_then(Value::as_object)
+        .ok_or("Corrupted file or invalid format")?;
+
+    for root in roots.values().filter(|root| root.is_object()) {
+        chromium_folder(root, link_groups);
+    }
+
+    Ok(())
+}
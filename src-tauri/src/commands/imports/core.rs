@@ -2,36 +2,69 @@ use std::collections::{BTreeSet, VecDeque};
 
 use serde::Deserialize;
 
-use crate::types::{Link, LinkGroup, SaveData};
+use super::bookmarks;
+use crate::{
+    canonical::CanonicalizeConfig,
+    folders::{self, Folder},
+    rules::{self, StorableTagRule, TagRule},
+    tag_tree,
+    types::{sort_pinned_first, Link, LinkGroup, SaveData, Tags},
+};
+
+// Lines that can come right before a group's links in text files, holding its
+// title and when it was created, in milliseconds
+const HEADING_PREFIX: &str = "# ";
+const CREATED_PREFIX: &str = "Created: ";
 
 pub fn check_for_duplicates(
     file_as_string: String,
     link_groups: &VecDeque<LinkGroup>,
+    canonicalize_config: &CanonicalizeConfig,
 ) -> Result<bool, String> {
     let mut import_buffer = VecDeque::<LinkGroup>::new();
 
     match &file_as_string[0..1] {
-        "h" => one_tab_import(file_as_string, &mut import_buffer),
-        "{" => json_import(file_as_string, &mut import_buffer, None),
+        "h" => one_tab_import(file_as_string, &mut import_buffer).map(|_| None),
+        "<" => bookmarks::netscape_import(file_as_string, &mut import_buffer).map(|_| None),
+        "{" if bookmarks::is_chromium(&file_as_string) => {
+            bookmarks::chromium_import(file_as_string, &mut import_buffer).map(|_| None)
+        }
+        "{" => json_import(file_as_string, &mut import_buffer, None, None, None),
         _ => Err("Corrupted file or invalid format".to_string()),
     }?;
 
-    let existing_links = link_groups
+    let existing_links = canonical_keys(link_groups, canonicalize_config);
+
+    let duplicate_exists = import_buffer
         .iter()
         .flat_map(|group| &group.links)
-        .map(|link| &link.uri)
-        .collect::<BTreeSet<&String>>();
-
-    let duplicate_exists = import_buffer.iter().any(|group| {
-        group
-            .links
-            .iter()
-            .any(|link| existing_links.contains(&link.uri))
-    });
+        .any(|link| is_existing(link, &existing_links, canonicalize_config));
 
     Ok(duplicate_exists)
 }
 
+// Keys of every URI links are known by, including the aliases of expanded links
+fn canonical_keys(
+    link_groups: &VecDeque<LinkGroup>,
+    canonicalize_config: &CanonicalizeConfig,
+) -> BTreeSet<String> {
+    link_groups
+        .iter()
+        .flat_map(|group| &group.links)
+        .flat_map(|link| link.uris())
+        .map(|uri| canonicalize_config.canonicalize(uri))
+        .collect()
+}
+
+fn is_existing(
+    link: &Link,
+    existing_links: &BTreeSet<String>,
+    canonicalize_config: &CanonicalizeConfig,
+) -> bool {
+    link.uris()
+        .any(|uri| existing_links.contains(&canonicalize_config.canonicalize(uri)))
+}
+
 #[derive(Deserialize)]
 pub enum ImportStrategy {
     KeepAll,
@@ -53,23 +86,51 @@ pub struct ImportConfig {
 pub fn import_from_file(
     file_as_string: String,
     link_groups: &mut VecDeque<LinkGroup>,
-    tags: &mut BTreeSet<String>,
+    tags: &mut Tags,
+    canonicalize_config: &mut CanonicalizeConfig,
+    tag_rules: &mut Vec<TagRule>,
+    folders: &mut Vec<Folder>,
     config: ImportConfig,
 ) -> Result<bool, String> {
+    // Checked before anything is imported, as the rules are applied once the groups
+    // have already been added
+    rules::compile_enabled(tag_rules)?;
+
     let mut import_buffer = VecDeque::<LinkGroup>::new();
 
-    match &file_as_string[0..1] {
-        "h" => one_tab_import(file_as_string, &mut import_buffer),
-        "{" => json_import(file_as_string, &mut import_buffer, Some(tags)),
+    let imported_config = match &file_as_string[0..1] {
+        "h" => one_tab_import(file_as_string, &mut import_buffer).map(|_| None),
+        "<" => bookmarks::netscape_import(file_as_string, &mut import_buffer).map(|_| None),
+        "{" if bookmarks::is_chromium(&file_as_string) => {
+            bookmarks::chromium_import(file_as_string, &mut import_buffer).map(|_| None)
+        }
+        "{" => json_import(
+            file_as_string,
+            &mut import_buffer,
+            Some(tags),
+            Some(tag_rules),
+            Some(folders),
+        ),
         _ => Err("Corrupted file or invalid format".to_string()),
     }?;
 
-    let existing_links = link_groups
+    // The imported tracking parameters are added to the current ones rather than
+    // replacing them, so an import never undoes the user's own settings
+    if let Some(imported_config) = imported_config {
+        for param in imported_config.tracking_params {
+            if !canonicalize_config.tracking_params.contains(&param) {
+                canonicalize_config.tracking_params.push(param);
+            }
+        }
+    }
+
+    let existing_links = canonical_keys(link_groups, canonicalize_config);
+
+    let imported_ids = import_buffer
         .iter()
         .flat_map(|group| &group.links)
-        .map(|link| &link.uri)
-        .cloned()
-        .collect::<BTreeSet<String>>();
+        .map(|link| link.id)
+        .collect::<BTreeSet<_>>();
 
     {
         let mut add_group: Box<dyn FnMut(LinkGroup)> = {
@@ -100,20 +161,9 @@ pub fn import_from_file(
                 for group in iter {
                     let mut mut_group = group.clone();
 
-                    let link_iter: Box<dyn Iterator<Item = &Link>> = {
-                        let iter = group.links.iter();
-
-                        match config.position {
-                            InsertPosition::Before => Box::new(iter.rev()),
-                            InsertPosition::After => Box::new(iter.into_iter()),
-                        }
-                    };
-
-                    for (index, link) in link_iter.enumerate() {
-                        if existing_links.contains(&link.uri) {
-                            mut_group.links.remove(index);
-                        }
-                    }
+                    mut_group
+                        .links
+                        .retain(|link| !is_existing(link, &existing_links, canonicalize_config));
 
                     if !mut_group.links.is_empty() {
                         add_group(mut_group);
@@ -123,12 +173,42 @@ pub fn import_from_file(
         }
     }
 
+    sort_pinned_first(link_groups);
+
+    // Tags read from bookmark files still need adding to the tag list
+    for link in link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+        .filter(|link| imported_ids.contains(&link.id))
     {
-        let mut set = BTreeSet::<&String>::new();
+        tag_tree::rewrite_tags(&mut link.tags, |tag| tag_tree::resolve_alias(tags, tag));
+
+        for tag in link.tags.iter() {
+            tags.entry(tag.to_owned()).or_default();
+        }
+    }
+
+    // Only the imported links are tagged, the rest of the library is left as it was
+    {
+        let compiled = rules::compile_enabled(tag_rules)?;
+
+        rules::apply(&compiled, link_groups, tags, false, |link| {
+            imported_ids.contains(&link.id)
+        });
+    }
+
+    {
+        let mut set = BTreeSet::<String>::new();
 
         for group in link_groups.iter() {
             for link in group.links.iter() {
-                let unique = set.insert(&link.uri);
+                // A link's own aliases can canonicalize to the same key as its URI
+                let keys = link
+                    .uris()
+                    .map(|uri| canonicalize_config.canonicalize(uri))
+                    .collect::<BTreeSet<_>>();
+
+                let unique = keys.into_iter().all(|key| set.insert(key));
 
                 if !unique {
                     return Ok(true);
@@ -145,6 +225,8 @@ fn one_tab_import(
     link_groups: &mut VecDeque<LinkGroup>,
 ) -> Result<(), String> {
     let mut link_buf = VecDeque::<Link>::new();
+    // Whether the lines being read still describe the group at the front
+    let mut describing = false;
 
     let lines = file_as_string.lines().rev();
 
@@ -156,17 +238,40 @@ fn one_tab_import(
                 return Err(format!("Invalid URI: '{}'", uri));
             }
 
-            let title = title.trim().to_string();
-
-            if title.is_empty() {
-                return Err(format!("Missing title for uri: '{}'", uri));
-            }
+            // Missing titles fall back to the URI, which the metadata fetcher treats as
+            // a title to be replaced
+            let title = match title.trim() {
+                "" => uri.to_owned(),
+                title => title.to_string(),
+            };
 
             link_buf.push_front(Link::new(uri, title, Vec::new()));
-        } else if !link_buf.is_empty() {
-            link_groups.push_front(LinkGroup::new(link_buf.clone()));
+            describing = false;
+
+            continue;
+        }
 
+        if !link_buf.is_empty() {
+            link_groups.push_front(LinkGroup::new(link_buf.clone()));
             link_buf = VecDeque::<Link>::new();
+            describing = true;
+        }
+
+        // Lines are read bottom up, so the heading and creation time come after the
+        // links they describe
+        let Some(group) = link_groups.front_mut().filter(|_| describing) else {
+            continue;
+        };
+
+        if let Some(title) = line.strip_prefix(HEADING_PREFIX) {
+            group.title = Some(title.trim().to_string()).filter(|title| !title.is_empty());
+        } else if let Some(created_at) = line
+            .strip_prefix(CREATED_PREFIX)
+            .and_then(|created_at| created_at.trim().parse::<u64>().ok())
+        {
+            group.created_at = Some(created_at);
+        } else {
+            describing = false;
         }
     }
 
@@ -180,22 +285,72 @@ fn one_tab_import(
 fn json_import(
     file_as_string: String,
     link_groups: &mut VecDeque<LinkGroup>,
-    tags: Option<&mut BTreeSet<String>>,
-) -> Result<(), String> {
+    mut tags: Option<&mut Tags>,
+    tag_rules: Option<&mut Vec<TagRule>>,
+    folders: Option<&mut Vec<Folder>>,
+) -> Result<Option<CanonicalizeConfig>, String> {
     let save_data = serde_json::from_str::<SaveData>(&file_as_string)
         .or(Err("Corrupted file or invalid format"))?;
 
-    save_data
+    let (folder_positions, mut imported_groups): (Vec<_>, Vec<_>) = save_data
         .link_groups
-        .iter()
-        .rev()
-        .for_each(|link| link_groups.push_front(link.to_owned().into()));
+        .into_iter()
+        .map(|storable| (storable.folder, LinkGroup::from(storable)))
+        .unzip();
 
-    if let Some(tags) = tags {
-        save_data.tags.iter().for_each(|tag| {
-            tags.insert(tag.to_owned());
-        });
+    if let Some(tags) = tags.as_deref_mut() {
+        for tag in save_data.tags {
+            let name = tag_tree::resolve(tags, &tag.name);
+
+            tags.entry(name).or_default().merge(tag.info);
+        }
+
+        // Imported links can use names that are aliases here
+        for link in imported_groups
+            .iter_mut()
+            .flat_map(|group| group.links.iter_mut())
+        {
+            tag_tree::rewrite_tags(&mut link.tags, |tag| tag_tree::resolve_alias(tags, tag));
+        }
     }
 
-    Ok(())
+    if let (Some(tag_rules), Some(tags)) = (tag_rules, tags.as_deref()) {
+        // Rules that don't validate, such as a hand-edited broken regex, are skipped
+        // rather than failing every later import and new link
+        for rule in save_data.tag_rules {
+            let Ok(rule_tags) = rules::validate(&rule.matcher, &rule.tags, tags) else {
+                continue;
+            };
+
+            let rule = StorableTagRule {
+                tags: rule_tags,
+                ..rule
+            };
+
+            let exists = tag_rules
+                .iter()
+                .any(|existing| existing.matcher == rule.matcher && existing.tags == rule.tags);
+
+            if !exists {
+                tag_rules.push(rule.into());
+            }
+        }
+    }
+
+    // Loaded last so that an import failing any earlier doesn't leave its folders
+    // behind
+    let folder_ids = folders
+        .map(|folders| folders::load(&save_data.folders, folders))
+        .unwrap_or_default();
+
+    for (group, position) in imported_groups.iter_mut().zip(folder_positions) {
+        group.folder_id = position.and_then(|position| folder_ids.get(&position).copied());
+    }
+
+    imported_groups
+        .into_iter()
+        .rev()
+        .for_each(|group| link_groups.push_front(group));
+
+    Ok(save_data.canonicalize)
 }
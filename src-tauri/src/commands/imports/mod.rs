@@ -2,12 +2,20 @@ use core::ImportConfig;
 
 use tauri::State;
 
-use crate::types::{LinksContainer, TagsContainer};
+use crate::types::{
+    CanonicalizeContainer, FoldersContainer, HistoryContainer, LinksContainer, TagRulesContainer,
+    TagsContainer,
+};
 
+mod bookmarks;
 mod core;
 
 #[tauri::command]
-pub fn check_for_duplicates(links: State<LinksContainer>, path: String) -> Result<bool, String> {
+pub fn check_for_duplicates(
+    links: State<LinksContainer>,
+    canonicalize: State<CanonicalizeContainer>,
+    path: String,
+) -> Result<bool, String> {
     let file_as_string = std::fs::read_to_string(path).map_err(|e| match e.kind() {
         std::io::ErrorKind::InvalidData => "Unable to read file".to_string(),
         _ => e.to_string(),
@@ -20,13 +28,21 @@ pub fn check_for_duplicates(links: State<LinksContainer>, path: String) -> Resul
     let mut links_lock = links.lock().map_err(|e| e.to_string())?;
     let link_groups = links_lock.get_mut();
 
-    core::check_for_duplicates(file_as_string, link_groups)
+    let canonicalize_lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = canonicalize_lock.borrow();
+
+    core::check_for_duplicates(file_as_string, link_groups, &canonicalize_config)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn import_from_file(
     links: State<LinksContainer>,
     tags: State<TagsContainer>,
+    canonicalize: State<CanonicalizeContainer>,
+    tag_rules: State<TagRulesContainer>,
+    folders: State<FoldersContainer>,
+    history: State<HistoryContainer>,
     path: String,
     config: ImportConfig,
 ) -> Result<bool, String> {
@@ -45,5 +61,31 @@ pub fn import_from_file(
     let mut tags_lock = tags.lock().map_err(|e| e.to_string())?;
     let tags = tags_lock.get_mut();
 
-    core::import_from_file(file_as_string, link_groups, tags, config)
+    let mut canonicalize_lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = canonicalize_lock.get_mut();
+
+    let mut rules_lock = tag_rules.lock().map_err(|e| e.to_string())?;
+    let tag_rules = rules_lock.get_mut();
+
+    let mut folders_lock = folders.lock().map_err(|e| e.to_string())?;
+    let folders = folders_lock.get_mut();
+
+    let mut history_lock = history.lock().map_err(|e| e.to_string())?;
+    let history = history_lock.get_mut();
+
+    let recording = history.record("Import", link_groups, tags);
+
+    let imported = core::import_from_file(
+        file_as_string,
+        link_groups,
+        tags,
+        canonicalize_config,
+        tag_rules,
+        folders,
+        config,
+    )?;
+
+    history.finish(recording, link_groups, tags);
+
+    Ok(imported)
 }
@@ -0,0 +1,8 @@
+use tauri::State;
+
+use crate::{jobs, types::JobsContainer};
+
+#[tauri::command]
+pub fn cancel_job(jobs: State<JobsContainer>, name: String) -> Result<(), String> {
+    jobs::cancel(&jobs, &name)
+}
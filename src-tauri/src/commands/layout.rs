@@ -0,0 +1,567 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::{
+    queries::sort::{self, SortKey},
+    similarity,
+    tag_tree::{self, SEPARATOR},
+    types::{
+        CanonicalizeContainer, HistoryContainer, Link, LinkGroup, LinkGroups, LinksContainer,
+        TagsContainer,
+    },
+};
+
+fn find_group(link_groups: &LinkGroups, id: usize) -> Result<usize, String> {
+    link_groups
+        .iter()
+        .position(|link_group| link_group.id == id)
+        .ok_or(format!("Group {} wasn't found", id))
+}
+
+// Moves the links of every group into the first one, in the order the groups were
+// given. With `dedupe` only the first copy of a link is kept, taking the tags of
+// the others
+#[tauri::command]
+pub fn merge_groups(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    canonicalize: State<CanonicalizeContainer>,
+    history: State<HistoryContainer>,
+    group_ids: Vec<usize>,
+    dedupe: bool,
+) -> Result<usize, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let canonicalize_lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = canonicalize_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let (&target_id, sources) = group_ids.split_first().ok_or("No groups to merge")?;
+
+    let mut seen = BTreeSet::<usize>::new();
+
+    if let Some(id) = group_ids.iter().find(|id| !seen.insert(**id)) {
+        return Err(format!("Group {} was given more than once", id));
+    }
+
+    for id in group_ids.iter() {
+        let position = find_group(&link_groups, *id)?;
+
+        if *id != target_id {
+            link_groups[position].check_unlocked()?;
+        }
+    }
+
+    let recording = history.record("Merge groups", &link_groups, &tags);
+
+    let mut merged = Vec::<Link>::new();
+
+    for id in group_ids.iter() {
+        let position = find_group(&link_groups, *id)?;
+
+        merged.extend(link_groups[position].links.drain(..));
+    }
+
+    link_groups.retain(|group| !sources.contains(&group.id));
+
+    if dedupe {
+        let mut first_copies = BTreeMap::<String, usize>::new();
+        let mut deduped = Vec::<Link>::with_capacity(merged.len());
+
+        for link in merged {
+            let key = canonicalize_config.canonicalize(&link.uri);
+
+            match first_copies.get(&key) {
+                Some(&index) => {
+                    let kept = &mut deduped[index];
+
+                    if link.tags.iter().any(|tag| !kept.tags.contains(tag)) {
+                        tag_tree::union(&mut kept.tags, &link.tags);
+                        kept.touch();
+                    }
+                }
+                None => {
+                    first_copies.insert(key, deduped.len());
+                    deduped.push(link);
+                }
+            }
+        }
+
+        merged = deduped;
+    }
+
+    let target = find_group(&link_groups, target_id)?;
+    link_groups[target].links = merged.into();
+    link_groups[target].touch();
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(target_id)
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SplitMode {
+    // Links from `position` onwards move to a new group
+    AtPosition { position: usize },
+    Chunks { size: usize },
+    Domain,
+}
+
+// Splits the group into several, placed where it was and in the same folder, and
+// returns their ids with the original group first
+#[tauri::command]
+pub fn split_group(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+    mode: SplitMode,
+) -> Result<Vec<usize>, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let position = find_group(&link_groups, id)?;
+    let group = &link_groups[position];
+
+    group.check_unlocked()?;
+
+    let links = group.links.iter().cloned().collect::<Vec<_>>();
+
+    let parts: Vec<(Option<String>, Vec<Link>)> = match mode {
+        SplitMode::AtPosition { position } => {
+            if position == 0 || position >= links.len() {
+                return Err(format!(
+                    "Can't split a group of {} links at {}",
+                    links.len(),
+                    position
+                ));
+            }
+
+            let (first, rest) = links.split_at(position);
+
+            vec![(None, first.to_vec()), (None, rest.to_vec())]
+        }
+        SplitMode::Chunks { size } => {
+            if size == 0 {
+                return Err("Chunks need at least one link".to_string());
+            }
+
+            links
+                .chunks(size)
+                .map(|chunk| (None, chunk.to_vec()))
+                .collect()
+        }
+        // Groups are named after their domain and follow the order domains first appear in
+        SplitMode::Domain => {
+            let mut domains = Vec::<(String, Vec<Link>)>::new();
+
+            for link in links {
+                let domain = link.domain().to_string();
+
+                match domains.iter_mut().find(|(existing, _)| *existing == domain) {
+                    Some((_, links)) => links.push(link),
+                    None => domains.push((domain, vec![link])),
+                }
+            }
+
+            domains
+                .into_iter()
+                .map(|(domain, links)| (Some(domain), links))
+                .collect()
+        }
+    };
+
+    if parts.len() < 2 {
+        return Ok(vec![id]);
+    }
+
+    let recording = history.record("Split group", &link_groups, &tags);
+
+    let mut ids = Vec::<usize>::with_capacity(parts.len());
+
+    for (index, (title, links)) in parts.into_iter().enumerate() {
+        if index == 0 {
+            let group = &mut link_groups[position];
+
+            group.links = links.into();
+            group.title = title.or(group.title.take());
+            group.touch();
+
+            ids.push(group.id);
+            continue;
+        }
+
+        let mut group = LinkGroup::new(links.into());
+
+        group.title = title;
+        group.folder_id = link_groups[position].folder_id;
+        group.pinned = link_groups[position].pinned;
+
+        ids.push(group.id);
+        link_groups.insert(position + index, group);
+    }
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(ids)
+}
+
+#[tauri::command]
+pub fn sort_group(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+    sort_by: String,
+    sort_direction: String,
+) -> Result<(), String> {
+    let sort_key = match sort_by.as_str() {
+        "title" | "uri" | "domain" | "date_added" | "last_updated" => {
+            SortKey::try_from(sort_by.as_str())?
+        }
+        _ => return Err(format!("Groups can't be sorted by '{}'", sort_by)),
+    };
+    let descending = sort::is_descending(&sort_direction)?;
+
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let position = find_group(&link_groups, id)?;
+
+    link_groups[position].check_unlocked()?;
+
+    let recording = history.record("Sort group", &link_groups, &tags);
+
+    let group = &mut link_groups[position];
+
+    let mut sorted = std::mem::take(&mut group.links)
+        .into_iter()
+        .enumerate()
+        .collect::<Vec<_>>();
+
+    let owner: &LinkGroup = group;
+
+    sorted.sort_by(|(a_pos, a), (b_pos, b)| {
+        let ordering = sort_key.compare((*a_pos, owner, a), (*b_pos, owner, b));
+
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+        .then_with(|| a_pos.cmp(b_pos))
+    });
+
+    group.links = sorted.into_iter().map(|(_, link)| link).collect();
+    group.touch();
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LayoutStrategy {
+    Domain,
+    TopLevelTag,
+    SimilarTitles { threshold: Option<f64> },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProposedGroup {
+    title: String,
+    link_ids: Vec<usize>,
+    // Existing group the new one takes the place of, keeping its id, description,
+    // color, flags and folder. Groups no proposal takes the place of lose those
+    #[serde(default)]
+    group_id: Option<usize>,
+}
+
+// Groups links sharing a key, ordered by the key
+fn group_by_key<F>(links: &[&Link], key: F) -> Vec<ProposedGroup>
+where
+    F: Fn(&Link) -> String,
+{
+    let mut groups = BTreeMap::<String, Vec<usize>>::new();
+
+    for link in links {
+        groups.entry(key(link)).or_default().push(link.id);
+    }
+
+    groups
+        .into_iter()
+        .map(|(title, link_ids)| ProposedGroup {
+            title,
+            link_ids,
+            group_id: None,
+        })
+        .collect()
+}
+
+// Each unlocked group is taken over by the proposed group holding most of its
+// links, unless another group has already taken that one over
+fn assign_existing_groups(link_groups: &LinkGroups, proposal: &mut [ProposedGroup]) {
+    for group in link_groups.iter().filter(|group| !group.locked) {
+        let shared = |proposed: &ProposedGroup| {
+            group
+                .links
+                .iter()
+                .filter(|link| proposed.link_ids.contains(&link.id))
+                .count()
+        };
+
+        let best = proposal
+            .iter_mut()
+            .filter(|proposed| proposed.group_id.is_none())
+            .map(|proposed| (shared(proposed), proposed))
+            .filter(|(shared, _)| *shared > 0)
+            .max_by_key(|(shared, _)| *shared);
+
+        if let Some((_, proposed)) = best {
+            proposed.group_id = Some(group.id);
+        }
+    }
+}
+
+// Proposes a new layout for every link outside locked groups, to be passed on to
+// `apply_layout` as is or after changes
+#[tauri::command]
+pub fn propose_layout(
+    links: State<LinksContainer>,
+    strategy: LayoutStrategy,
+) -> Result<Vec<ProposedGroup>, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let movable = link_groups
+        .iter()
+        .filter(|group| !group.locked)
+        .flat_map(|group| &group.links)
+        .collect::<Vec<_>>();
+
+    let mut proposal = match strategy {
+        LayoutStrategy::Domain => group_by_key(&movable, |link| {
+            let domain = link.domain().to_lowercase();
+
+            domain.strip_prefix("www.").unwrap_or(&domain).to_string()
+        }),
+        LayoutStrategy::TopLevelTag => group_by_key(&movable, |link| match link.tags.first() {
+            Some(tag) => tag.split(SEPARATOR).next().unwrap_or(tag).to_string(),
+            None => "Untagged".to_string(),
+        }),
+        LayoutStrategy::SimilarTitles { threshold } => {
+            let threshold = threshold.unwrap_or(0.5);
+
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err(format!(
+                    "Threshold must be between 0 and 1, got {}",
+                    threshold
+                ));
+            }
+
+            let mut clusters = similarity::find_clusters(&movable, threshold);
+
+            clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.members.len()));
+
+            let clustered = clusters
+                .iter()
+                .flat_map(|cluster| &cluster.members)
+                .copied()
+                .collect::<BTreeSet<_>>();
+
+            // Clusters are named after their first link, and anything without a
+            // similar title ends up together at the end
+            let mut proposal = clusters
+                .into_iter()
+                .map(|cluster| ProposedGroup {
+                    title: movable[cluster.members[0]].title.to_owned(),
+                    link_ids: cluster
+                        .members
+                        .iter()
+                        .map(|index| movable[*index].id)
+                        .collect(),
+                    group_id: None,
+                })
+                .collect::<Vec<_>>();
+
+            let rest = movable
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !clustered.contains(index))
+                .map(|(_, link)| link.id)
+                .collect::<Vec<_>>();
+
+            if !rest.is_empty() {
+                proposal.push(ProposedGroup {
+                    title: "Other".to_string(),
+                    link_ids: rest,
+                    group_id: None,
+                });
+            }
+
+            proposal
+        }
+    };
+
+    assign_existing_groups(&link_groups, &mut proposal);
+
+    Ok(proposal)
+}
+
+// Replaces every unlocked group with the proposed ones, as one undoable operation.
+// Locked groups keep their positions, and links added since the proposal was made
+// are kept in a group at the end
+#[tauri::command]
+pub fn apply_layout(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    groups: Vec<ProposedGroup>,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let mut movable = link_groups
+        .iter()
+        .filter(|group| !group.locked)
+        .flat_map(|group| &group.links)
+        .map(|link| (link.id, link.clone()))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut replaced = BTreeSet::<usize>::new();
+
+    for id in groups.iter().filter_map(|group| group.group_id) {
+        if !replaced.insert(id) {
+            return Err(format!("Group {} is taken over more than once", id));
+        }
+
+        if !link_groups
+            .iter()
+            .any(|group| group.id == id && !group.locked)
+        {
+            return Err(format!("Group {} wasn't found outside locked groups", id));
+        }
+    }
+
+    let mut seen = BTreeSet::<usize>::new();
+
+    for id in groups.iter().flat_map(|group| &group.link_ids) {
+        if !seen.insert(*id) {
+            return Err(format!("Link {} is in more than one group", id));
+        }
+
+        if !movable.contains_key(id) {
+            return Err(format!("Link {} wasn't found outside locked groups", id));
+        }
+    }
+
+    // Links left out keep their original order
+    let left_out = link_groups
+        .iter()
+        .filter(|group| !group.locked)
+        .flat_map(|group| &group.links)
+        .filter(|link| !seen.contains(&link.id))
+        .map(|link| link.id)
+        .collect::<Vec<_>>();
+
+    let recording = history.record("Reorganize", &link_groups, &tags);
+
+    let mut new_groups = groups
+        .into_iter()
+        .filter(|group| !group.link_ids.is_empty())
+        .map(|group| (Some(group.title), group.link_ids, group.group_id))
+        .collect::<Vec<_>>();
+
+    if !left_out.is_empty() {
+        new_groups.push((None, left_out, None));
+    }
+
+    let mut new_groups = new_groups
+        .into_iter()
+        .map(|(title, link_ids, group_id)| {
+            let links = link_ids
+                .iter()
+                .filter_map(|id| movable.remove(id))
+                .collect();
+
+            // Taking over a group keeps everything but its title and links
+            let mut group = match group_id.and_then(|id| find_group(&link_groups, id).ok()) {
+                Some(position) => LinkGroup {
+                    links,
+                    ..link_groups[position].clone()
+                },
+                None => LinkGroup::new(links),
+            };
+
+            group.title = title;
+            group.touch();
+
+            group
+        })
+        .collect::<Vec<_>>();
+
+    // Groups taking over pinned ones are pinned too, and come before the other
+    // unlocked ones, while locked groups keep their positions
+    new_groups.sort_by_key(|group| !group.pinned);
+
+    let locked = link_groups
+        .iter()
+        .enumerate()
+        .filter(|(_, group)| group.locked)
+        .map(|(position, group)| (position, group.clone()))
+        .collect::<Vec<_>>();
+
+    let total = locked.len() + new_groups.len();
+    let mut locked = locked.into_iter().peekable();
+    let mut new_groups = new_groups.into_iter().peekable();
+
+    link_groups.clear();
+
+    // Locked groups go back where they were, or at the end when there are fewer
+    // groups than before
+    while link_groups.len() < total {
+        let locked_next = match (locked.peek(), new_groups.peek()) {
+            (Some((position, _)), Some(_)) => *position <= link_groups.len(),
+            (locked, _) => locked.is_some(),
+        };
+
+        let group = match locked_next {
+            true => locked.next().map(|(_, group)| group),
+            false => new_groups.next(),
+        };
+
+        link_groups.extend(group);
+    }
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
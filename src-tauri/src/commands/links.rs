@@ -2,11 +2,20 @@ use std::collections::VecDeque;
 
 use tauri::State;
 
-use crate::types::{Link, LinksContainer};
+use crate::{
+    rules,
+    types::{
+        now, validate_title, validate_uri, CanonicalizeContainer, HistoryContainer, Link,
+        LinkGroups, LinksContainer, PageContent, TagRulesContainer, TagsContainer,
+    },
+};
 
 #[tauri::command]
 pub fn add_link(
     links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    tag_rules: State<TagRulesContainer>,
+    history: State<HistoryContainer>,
     group_id: usize,
     uri: String,
     title: String,
@@ -14,29 +23,259 @@ pub fn add_link(
     let lock = links.lock().map_err(|e| e.to_string())?;
     let mut link_groups = lock.borrow_mut();
 
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let rules_lock = tag_rules.lock().map_err(|e| e.to_string())?;
+    let tag_rules = rules_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    // Compiled first so that a broken rule leaves the library untouched
+    let compiled = rules::compile_enabled(&tag_rules)?;
+
+    let uri = validate_uri(&uri)?;
+    let title = validate_title(&title)?;
+
+    let recording = history.record("Add link", &link_groups, &tags);
+
     let group = link_groups
         .iter_mut()
         .find(|link_group| link_group.id == group_id)
         .ok_or(format!("Group {} wasn't found", group_id))?;
 
-    group
-        .links
-        .push_front(Link::new(uri, title, VecDeque::new().into()));
+    let link = Link::new(uri, title, VecDeque::new().into());
+    let link_id = link.id;
+
+    group.links.push_front(link);
+    group.touch();
+
+    rules::apply(&compiled, &mut link_groups, &mut tags, false, |link| {
+        link.id == link_id
+    });
+
+    history.finish(recording, &link_groups, &tags);
 
     Ok(())
 }
 
+// Edits a link without moving it or touching its tags. Returns the ids of any other
+// links it's now a duplicate of
 #[tauri::command]
-pub fn remove_link(links: State<LinksContainer>, id: usize) -> Result<(), String> {
+pub fn update_link(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    canonicalize: State<CanonicalizeContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+    title: Option<String>,
+    uri: Option<String>,
+) -> Result<Vec<usize>, String> {
     let lock = links.lock().map_err(|e| e.to_string())?;
     let mut link_groups = lock.borrow_mut();
 
-    link_groups.iter_mut().for_each(|link_group| {
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let canonicalize_lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = canonicalize_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Edit link", &link_groups, &tags);
+
+    let title = title.as_deref().map(validate_title).transpose()?;
+    let uri = uri.as_deref().map(validate_uri).transpose()?;
+
+    let link = link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+        .find(|link| link.id == id)
+        .ok_or(format!("Link {} wasn't found", id))?;
+
+    if let Some(title) = title {
+        link.title = title;
+    }
+
+    if let Some(uri) = uri {
+        link.set_uri(uri);
+    }
+
+    link.touch();
+
+    let link = link.clone();
+
+    history.finish(recording, &link_groups, &tags);
+
+    let index = canonicalize_config.index(&link_groups);
+
+    Ok(canonicalize_config.duplicates_of(&index, &link))
+}
+
+#[tauri::command]
+pub fn remove_link(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Remove link", &link_groups, &tags);
+
+    for link_group in link_groups.iter_mut() {
         let item_to_remove = link_group.links.iter().position(|link| link.id == id);
 
         if let Some(item) = item_to_remove {
+            link_group.check_unlocked()?;
             link_group.links.remove(item);
+            link_group.touch();
         }
+    }
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_link_content(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+    content: Option<PageContent>,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Set page content", &link_groups, &tags);
+
+    let link = link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+        .find(|link| link.id == id)
+        .ok_or(format!("Link {} wasn't found", id))?;
+
+    link.content = content;
+    link.touch();
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
+
+// Blank notes are removed
+#[tauri::command]
+pub fn set_link_note(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+    note: Option<String>,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Edit note", &link_groups, &tags);
+
+    let link = link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+        .find(|link| link.id == id)
+        .ok_or(format!("Link {} wasn't found", id))?;
+
+    link.note = note.filter(|note| !note.trim().is_empty());
+    link.touch();
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn mark_group_opened(
+    links: State<LinksContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let opened_at = now();
+
+    let set_opened = |link_groups: &mut LinkGroups| {
+        let group = link_groups.iter_mut().find(|group| group.id == id)?;
+
+        group.last_opened_at = Some(opened_at);
+
+        Some(())
+    };
+
+    set_opened(&mut link_groups).ok_or(format!("Group {} wasn't found", id))?;
+    history.apply_to_all(|link_groups| {
+        set_opened(link_groups);
+    });
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn mark_link_opened(
+    links: State<LinksContainer>,
+    history: State<HistoryContainer>,
+    id: usize,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let opened_at = now();
+
+    // Opening a link also counts as opening the group it's in
+    let set_opened = |link_groups: &mut LinkGroups| {
+        let group = link_groups
+            .iter_mut()
+            .find(|group| group.links.iter().any(|link| link.id == id))?;
+
+        group.last_opened_at = Some(opened_at);
+
+        let link = group.links.iter_mut().find(|link| link.id == id)?;
+
+        link.last_opened_at = Some(opened_at);
+
+        Some(())
+    };
+
+    set_opened(&mut link_groups).ok_or(format!("Link {} wasn't found", id))?;
+    history.apply_to_all(|link_groups| {
+        set_opened(link_groups);
     });
 
     Ok(())
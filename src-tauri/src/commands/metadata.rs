@@ -0,0 +1,79 @@
+use tauri::{AppHandle, State};
+
+use crate::{
+    jobs,
+    metadata::{self, TitleReplacement},
+    net::{self, FetchOptions, HostRateLimiter},
+    types::{HistoryContainer, JobsContainer, LinksContainer, TagsContainer},
+};
+
+#[tauri::command]
+pub fn fetch_metadata(
+    app: AppHandle,
+    links: State<LinksContainer>,
+    jobs: State<JobsContainer>,
+    ids: Option<Vec<usize>>,
+    low_quality_only: Option<bool>,
+    options: Option<FetchOptions>,
+) -> Result<usize, String> {
+    let options = options.unwrap_or_default();
+    let low_quality_only = low_quality_only.unwrap_or(false);
+
+    let targets = {
+        let lock = links.lock().map_err(|e| e.to_string())?;
+        let link_groups = lock.borrow();
+
+        link_groups
+            .iter()
+            .flat_map(|group| &group.links)
+            .filter(|link| ids.as_ref().is_none_or(|ids| ids.contains(&link.id)))
+            .filter(|link| !low_quality_only || metadata::is_low_quality_title(link))
+            .map(|link| (link.id, link.uri.to_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    let agent = net::build_agent(options.timeout(), metadata::MAX_REDIRECTS);
+    let rate_limiter = HostRateLimiter::new(options.host_interval());
+
+    jobs::spawn(
+        app,
+        &jobs,
+        metadata::JOB_NAME,
+        targets,
+        options.concurrency,
+        move |app, (link_id, uri)| {
+            rate_limiter.wait(&uri);
+
+            let fetched = net::fetch_html(&agent, &uri, metadata::MAX_BODY_BYTES)
+                .map(|page| metadata::parse(&page, &uri));
+
+            metadata::record(app, link_id, fetched)
+        },
+    )
+}
+
+#[tauri::command]
+pub fn replace_low_quality_titles(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    preview: Option<bool>,
+) -> Result<Vec<TitleReplacement>, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Replace titles", &link_groups, &tags);
+
+    let replacements =
+        metadata::replace_low_quality_titles(&mut link_groups, preview.unwrap_or(false));
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(replacements)
+}
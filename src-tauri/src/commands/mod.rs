@@ -1,15 +1,43 @@
+mod bulk;
+mod canonical;
+mod dupes;
 mod exports;
+mod favicons;
+mod folders;
 mod groups;
+mod health;
+mod history;
 mod imports;
+mod jobs;
+mod layout;
 mod links;
+mod metadata;
 mod purge;
+mod redirects;
 mod reorder;
+mod replace;
+mod rules;
+mod snapshots;
 mod tags;
 
+pub use bulk::*;
+pub use canonical::*;
+pub use dupes::*;
 pub use exports::*;
+pub use favicons::*;
+pub use folders::*;
 pub use groups::*;
+pub use health::*;
+pub use history::*;
 pub use imports::*;
+pub use jobs::*;
+pub use layout::*;
 pub use links::*;
+pub use metadata::*;
 pub use purge::*;
+pub use redirects::*;
 pub use reorder::*;
+pub use replace::*;
+pub use rules::*;
+pub use snapshots::*;
 pub use tags::*;
@@ -1,18 +1,39 @@
 use tauri::State;
 
-use crate::types::{LinksContainer, TagsContainer};
+use crate::{
+    tag_tree,
+    types::{HistoryContainer, LinksContainer, TagsContainer},
+};
 
 #[tauri::command]
-pub fn purge_links(links: State<LinksContainer>, tags: State<TagsContainer>) -> Result<(), String> {
+pub fn purge_links(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+) -> Result<(), String> {
     let mut links_lock = links.lock().map_err(|e| e.to_string())?;
     let links = links_lock.get_mut();
 
-    links.clear();
-
     let mut tags_lock = tags.lock().map_err(|e| e.to_string())?;
     let tags = tags_lock.get_mut();
 
-    tags.clear();
+    let mut history_lock = history.lock().map_err(|e| e.to_string())?;
+    let history = history_lock.get_mut();
+
+    let recording = history.record("Purge links", links, tags);
+
+    // Locked groups survive, along with the tags their links use
+    links.retain(|group| group.locked);
+
+    tags.retain(|tag, _| {
+        links
+            .iter()
+            .flat_map(|group| &group.links)
+            .flat_map(|link| &link.tags)
+            .any(|link_tag| tag_tree::is_within(link_tag, tag))
+    });
+
+    history.finish(recording, links, tags);
 
     Ok(())
 }
@@ -0,0 +1,55 @@
+use tauri::{AppHandle, State};
+
+use crate::{
+    jobs,
+    net::{self, FetchOptions, HostRateLimiter},
+    redirects,
+    types::{JobsContainer, LinksContainer},
+};
+
+// Without ids only links on known link shortener hosts are expanded
+#[tauri::command]
+pub fn expand_redirects(
+    app: AppHandle,
+    links: State<LinksContainer>,
+    jobs: State<JobsContainer>,
+    ids: Option<Vec<usize>>,
+    apply: Option<bool>,
+    options: Option<FetchOptions>,
+) -> Result<usize, String> {
+    let options = options.unwrap_or_default();
+    let apply = apply.unwrap_or(false);
+
+    let targets = {
+        let lock = links.lock().map_err(|e| e.to_string())?;
+        let link_groups = lock.borrow();
+
+        link_groups
+            .iter()
+            .flat_map(|group| &group.links)
+            .filter(|link| match ids {
+                Some(ref ids) => ids.contains(&link.id),
+                None => redirects::is_short_link(&link.uri),
+            })
+            .map(|link| (link.id, link.uri.to_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    let agent = net::build_agent(options.timeout(), 0);
+    let rate_limiter = HostRateLimiter::new(options.host_interval());
+
+    jobs::spawn(
+        app,
+        &jobs,
+        redirects::JOB_NAME,
+        targets,
+        options.concurrency,
+        move |app, (link_id, uri)| {
+            rate_limiter.wait(&uri);
+
+            let resolved = redirects::resolve(&agent, &uri);
+
+            redirects::record(app, link_id, uri, resolved, apply)
+        },
+    )
+}
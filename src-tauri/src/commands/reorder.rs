@@ -1,33 +1,52 @@
 use tauri::State;
 
-use crate::types::{Link, LinksContainer};
+use crate::types::{sort_pinned_first, HistoryContainer, Link, LinksContainer, TagsContainer};
 
 #[tauri::command]
 pub fn reorder_group(
     links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
     group_id: usize,
     position: usize,
 ) -> Result<(), String> {
     let lock = links.lock().map_err(|e| e.to_string())?;
     let mut link_groups = lock.borrow_mut();
 
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Move group", &link_groups, &tags);
+
     let group_pos = link_groups
         .iter()
         .position(|group| group.id == group_id)
         .ok_or("Group matching provided id not found")?;
 
+    link_groups[group_pos].check_unlocked()?;
+
     let group = link_groups
         .remove(group_pos)
         .expect("Matched group not found");
 
     link_groups.insert(position, group);
 
+    // Groups can't be moved across the pinned ones
+    sort_pinned_first(&mut link_groups);
+
+    history.finish(recording, &link_groups, &tags);
+
     Ok(())
 }
 
 #[tauri::command]
 pub fn reorder_link(
     links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
     link_id: usize,
     group_id: usize,
     position_in_group: usize,
@@ -35,6 +54,28 @@ pub fn reorder_link(
     let lock = links.lock().map_err(|e| e.to_string())?;
     let mut link_groups = lock.borrow_mut();
 
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Move link", &link_groups, &tags);
+
+    let target = link_groups
+        .iter()
+        .find(|group| group.id == group_id)
+        .ok_or("Group matching provided id not found")?;
+
+    target.check_unlocked()?;
+
+    let source = link_groups
+        .iter()
+        .find(|group| group.links.iter().any(|link| link.id == link_id))
+        .ok_or("Link matching provided id not found")?;
+
+    source.check_unlocked()?;
+
     let link: Link = {
         let mut matched_link: Option<Link> = None;
 
@@ -42,6 +83,7 @@ pub fn reorder_link(
             for (pos, link) in group.links.clone().iter().enumerate() {
                 if link.id == link_id {
                     matched_link = group.links.remove(pos);
+                    group.touch();
                 }
             }
         }
@@ -55,6 +97,9 @@ pub fn reorder_link(
         .ok_or("Group matching provided id not found")?;
 
     group.links.insert(position_in_group, link);
+    group.touch();
+
+    history.finish(recording, &link_groups, &tags);
 
     Ok(())
 }
@@ -0,0 +1,183 @@
+use regex::{NoExpand, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::types::{
+    validate_title, validate_uri, CanonicalizeContainer, HistoryContainer, LinksContainer,
+    TagsContainer,
+};
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplaceField {
+    Title,
+    Uri,
+}
+
+#[derive(Deserialize)]
+pub struct ReplaceQuery {
+    pattern: String,
+    // With `regex`, `$1` and `${name}` refer to the pattern's capture groups
+    replacement: String,
+    regex: bool,
+    case_sensitive: bool,
+    fields: Vec<ReplaceField>,
+}
+
+impl ReplaceQuery {
+    fn compile(&self) -> Result<Regex, String> {
+        if self.pattern.is_empty() {
+            return Err("Nothing to find".to_string());
+        }
+
+        if self.fields.is_empty() {
+            return Err("Pick titles, URIs or both to replace in".to_string());
+        }
+
+        let pattern = match self.regex {
+            true => self.pattern.to_owned(),
+            false => regex::escape(&self.pattern),
+        };
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map_err(|e| e.to_string())
+    }
+
+    fn replace(&self, pattern: &Regex, text: &str) -> String {
+        match self.regex {
+            true => pattern.replace_all(text, &self.replacement).into_owned(),
+            false => pattern
+                .replace_all(text, NoExpand(&self.replacement))
+                .into_owned(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Replacement {
+    id: usize,
+    field: ReplaceField,
+    before: String,
+    after: String,
+    // Replacements leaving an empty title or an invalid URI are listed but not applied
+    error: Option<String>,
+    // Other links a changed URI makes this one a duplicate of
+    duplicate_ids: Vec<usize>,
+}
+
+// Replaces matches in the titles and URIs of the given links, or every link when
+// there are no ids. With `dry_run` the replacements are only previewed, otherwise
+// they're applied as one undoable change
+#[tauri::command]
+pub fn find_and_replace(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    canonicalize: State<CanonicalizeContainer>,
+    history: State<HistoryContainer>,
+    query: ReplaceQuery,
+    link_ids: Option<Vec<usize>>,
+    dry_run: bool,
+) -> Result<Vec<Replacement>, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let canonicalize_lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = canonicalize_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let pattern = query.compile()?;
+
+    let mut replacements = Vec::<Replacement>::new();
+
+    for link in link_groups
+        .iter()
+        .flat_map(|group| &group.links)
+        .filter(|link| link_ids.as_ref().is_none_or(|ids| ids.contains(&link.id)))
+    {
+        for field in query.fields.iter().copied() {
+            let before = match field {
+                ReplaceField::Title => &link.title,
+                ReplaceField::Uri => &link.uri,
+            };
+
+            let after = query.replace(&pattern, before);
+
+            if after == *before {
+                continue;
+            }
+
+            let (after, error) = match field {
+                ReplaceField::Title => validate_title(&after),
+                ReplaceField::Uri => validate_uri(&after),
+            }
+            .map_or_else(|error| (after, Some(error)), |after| (after, None));
+
+            replacements.push(Replacement {
+                id: link.id,
+                field,
+                before: before.to_owned(),
+                after,
+                error,
+                duplicate_ids: Vec::new(),
+            });
+        }
+    }
+
+    // URIs are changed even in a dry run, on a copy, so duplicates can be found
+    // between links that are all being changed
+    let mut changed = link_groups.clone();
+
+    for replacement in replacements
+        .iter()
+        .filter(|replacement| replacement.error.is_none())
+    {
+        let link = changed
+            .iter_mut()
+            .flat_map(|group| group.links.iter_mut())
+            .find(|link| link.id == replacement.id)
+            .expect("Replacements are only made for existing links");
+
+        match replacement.field {
+            ReplaceField::Title => link.title = replacement.after.to_owned(),
+            ReplaceField::Uri => link.set_uri(replacement.after.to_owned()),
+        }
+
+        link.touch();
+    }
+
+    let index = canonicalize_config.index(&changed);
+
+    for replacement in replacements
+        .iter_mut()
+        .filter(|replacement| replacement.field == ReplaceField::Uri && replacement.error.is_none())
+    {
+        let link = changed
+            .iter()
+            .flat_map(|group| &group.links)
+            .find(|link| link.id == replacement.id)
+            .expect("Replacements are only made for existing links");
+
+        replacement.duplicate_ids = canonicalize_config.duplicates_of(&index, link);
+    }
+
+    let applicable = replacements
+        .iter()
+        .any(|replacement| replacement.error.is_none());
+
+    if !dry_run && applicable {
+        let recording = history.record("Find and replace", &link_groups, &tags);
+
+        *link_groups = changed;
+
+        history.finish(recording, &link_groups, &tags);
+    }
+
+    Ok(replacements)
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::{
+    rules::{self, RuleMatcher, TagRule},
+    types::{HistoryContainer, LinksContainer, TagRulesContainer, TagsContainer},
+};
+
+#[tauri::command]
+pub fn add_tag_rule(
+    tags: State<TagsContainer>,
+    tag_rules: State<TagRulesContainer>,
+    matcher: RuleMatcher,
+    rule_tags: Vec<String>,
+) -> Result<TagRule, String> {
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let rules_lock = tag_rules.lock().map_err(|e| e.to_string())?;
+    let mut tag_rules = rules_lock.borrow_mut();
+
+    let rule_tags = rules::validate(&matcher, &rule_tags, &tags)?;
+
+    let rule: TagRule = rules::StorableTagRule {
+        matcher,
+        tags: rule_tags,
+        enabled: true,
+    }
+    .into();
+
+    tag_rules.push(rule.clone());
+
+    Ok(rule)
+}
+
+#[tauri::command]
+pub fn update_tag_rule(
+    tags: State<TagsContainer>,
+    tag_rules: State<TagRulesContainer>,
+    id: usize,
+    matcher: RuleMatcher,
+    rule_tags: Vec<String>,
+    enabled: bool,
+) -> Result<(), String> {
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let rules_lock = tag_rules.lock().map_err(|e| e.to_string())?;
+    let mut tag_rules = rules_lock.borrow_mut();
+
+    let rule_tags = rules::validate(&matcher, &rule_tags, &tags)?;
+
+    let rule = tag_rules
+        .iter_mut()
+        .find(|rule| rule.id == id)
+        .ok_or(format!("Rule {} wasn't found", id))?;
+
+    rule.matcher = matcher;
+    rule.tags = rule_tags;
+    rule.enabled = enabled;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn remove_tag_rule(tag_rules: State<TagRulesContainer>, id: usize) -> Result<(), String> {
+    let lock = tag_rules.lock().map_err(|e| e.to_string())?;
+    let mut tag_rules = lock.borrow_mut();
+
+    let position = tag_rules
+        .iter()
+        .position(|rule| rule.id == id)
+        .ok_or(format!("Rule {} wasn't found", id))?;
+
+    tag_rules.remove(position);
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct RuleResult {
+    rule_id: usize,
+    link_ids: Vec<usize>,
+}
+
+// Runs the enabled rules, or only the given ones, across the whole library. With
+// `dry_run` nothing is changed and the result lists the links each rule would tag
+#[tauri::command]
+pub fn apply_tag_rules(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    tag_rules: State<TagRulesContainer>,
+    history: State<HistoryContainer>,
+    rule_ids: Option<Vec<usize>>,
+    dry_run: bool,
+) -> Result<Vec<RuleResult>, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let rules_lock = tag_rules.lock().map_err(|e| e.to_string())?;
+    let tag_rules = rules_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let compiled = match rule_ids {
+        Some(ids) => ids
+            .iter()
+            .map(|id| {
+                tag_rules
+                    .iter()
+                    .find(|rule| rule.id == *id)
+                    .ok_or(format!("Rule {} wasn't found", id))
+                    .and_then(rules::compile)
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => rules::compile_enabled(&tag_rules)?,
+    };
+
+    let recording = history.record("Apply tag rules", &link_groups, &tags);
+
+    let tagged = rules::apply(&compiled, &mut link_groups, &mut tags, dry_run, |_| true);
+
+    if !dry_run {
+        let tagged_ids = tagged.iter().flatten().collect::<BTreeSet<_>>();
+
+        for link in link_groups
+            .iter_mut()
+            .flat_map(|group| group.links.iter_mut())
+            .filter(|link| tagged_ids.contains(&link.id))
+        {
+            link.touch();
+        }
+    }
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(compiled
+        .iter()
+        .zip(tagged)
+        .map(|(compiled, link_ids)| RuleResult {
+            rule_id: compiled.rule.id,
+            link_ids,
+        })
+        .collect())
+}
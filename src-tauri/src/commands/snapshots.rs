@@ -0,0 +1,219 @@
+use std::{collections::BTreeSet, fs, path::PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::{
+    jobs,
+    net::{self, FetchOptions, HostRateLimiter},
+    snapshots,
+    types::{HistoryContainer, JobsContainer, Link, LinkGroups, LinksContainer},
+};
+
+#[tauri::command]
+pub fn archive_links(
+    app: AppHandle,
+    links: State<LinksContainer>,
+    jobs: State<JobsContainer>,
+    ids: Option<Vec<usize>>,
+    inline: Option<bool>,
+    options: Option<FetchOptions>,
+) -> Result<usize, String> {
+    let options = options.unwrap_or_default();
+    let inline = inline.unwrap_or(false);
+    let dir = snapshots::snapshots_dir(&app)?;
+
+    // Without ids only links that haven't been archived yet are queued
+    let targets = {
+        let lock = links.lock().map_err(|e| e.to_string())?;
+        let link_groups = lock.borrow();
+
+        link_groups
+            .iter()
+            .flat_map(|group| &group.links)
+            .filter(|link| match ids {
+                Some(ref ids) => ids.contains(&link.id),
+                None => link.snapshot.is_none(),
+            })
+            .map(|link| (link.id, link.uri.to_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    let agent = net::build_agent(options.timeout(), snapshots::MAX_REDIRECTS);
+    let rate_limiter = HostRateLimiter::new(options.host_interval());
+
+    jobs::spawn(
+        app,
+        &jobs,
+        snapshots::JOB_NAME,
+        targets,
+        options.concurrency,
+        move |app, (link_id, uri)| {
+            rate_limiter.wait(&uri);
+
+            snapshots::record(
+                app,
+                link_id,
+                snapshots::archive(&agent, &dir, link_id, &uri, inline),
+            )
+        },
+    )
+}
+
+#[tauri::command]
+pub fn open_snapshot(links: State<LinksContainer>, id: usize) -> Result<(), String> {
+    let path = {
+        let lock = links.lock().map_err(|e| e.to_string())?;
+        let link_groups = lock.borrow();
+
+        let link = link_groups
+            .iter()
+            .flat_map(|group| &group.links)
+            .find(|link| link.id == id)
+            .ok_or(format!("Link {} wasn't found", id))?;
+
+        link.snapshot
+            .as_ref()
+            .map(|snapshot| PathBuf::from(&snapshot.path))
+            .ok_or("Link hasn't been archived")?
+    };
+
+    if !path.is_file() {
+        return Err(format!("Snapshot file is missing: '{}'", path.display()));
+    }
+
+    // The shell scope only allows web URIs, so local files are opened directly
+    open::that(path).map_err(|e| e.to_string())
+}
+
+#[derive(Serialize, Default)]
+pub struct PruneResult {
+    removed_files: usize,
+    freed_bytes: u64,
+}
+
+#[tauri::command]
+pub fn delete_snapshots(
+    links: State<LinksContainer>,
+    history: State<HistoryContainer>,
+    ids: Vec<usize>,
+) -> Result<PruneResult, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let mut result = PruneResult::default();
+
+    for link in link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+        .filter(|link| ids.contains(&link.id))
+    {
+        if let Some(snapshot) = link.snapshot.take() {
+            if fs::remove_file(&snapshot.path).is_ok() {
+                result.removed_files += 1;
+                result.freed_bytes += snapshot.size;
+            }
+        }
+    }
+
+    history
+        .apply_to_all(|link_groups| forget_snapshots(link_groups, |link| ids.contains(&link.id)));
+
+    Ok(result)
+}
+
+// The files are gone, so undoing mustn't bring back links pointing at them
+fn forget_snapshots<F>(link_groups: &mut LinkGroups, filter: F)
+where
+    F: Fn(&Link) -> bool,
+{
+    for link in link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+        .filter(|link| filter(link))
+    {
+        link.snapshot = None;
+    }
+}
+
+// Files that no link points at are only removed when asked, since links are
+// held in memory and a library that hasn't been imported yet would look orphaned
+#[tauri::command]
+pub fn prune_snapshots(
+    app: AppHandle,
+    links: State<LinksContainer>,
+    history: State<HistoryContainer>,
+    older_than: Option<u64>,
+    remove_orphans: Option<bool>,
+) -> Result<PruneResult, String> {
+    let dir = snapshots::snapshots_dir(&app)?;
+
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let mut result = PruneResult::default();
+
+    if let Some(older_than) = older_than {
+        for link in link_groups
+            .iter_mut()
+            .flat_map(|group| group.links.iter_mut())
+        {
+            if link
+                .snapshot
+                .as_ref()
+                .is_some_and(|snapshot| snapshot.created_at < older_than)
+            {
+                let snapshot = link.snapshot.take().expect("Snapshot was just checked");
+
+                if fs::remove_file(&snapshot.path).is_ok() {
+                    result.removed_files += 1;
+                    result.freed_bytes += snapshot.size;
+                }
+            }
+        }
+
+        history.apply_to_all(|link_groups| {
+            forget_snapshots(link_groups, |link| {
+                link.snapshot
+                    .as_ref()
+                    .is_some_and(|snapshot| snapshot.created_at < older_than)
+            })
+        });
+    }
+
+    if remove_orphans.unwrap_or(false) {
+        let referenced = link_groups
+            .iter()
+            .flat_map(|group| &group.links)
+            .filter_map(|link| link.snapshot.as_ref())
+            .map(|snapshot| PathBuf::from(&snapshot.path))
+            .collect::<BTreeSet<_>>();
+
+        let mut removed = BTreeSet::<PathBuf>::new();
+
+        for (path, size) in snapshots::stored_files(&dir)? {
+            if !referenced.contains(&path) && fs::remove_file(&path).is_ok() {
+                result.removed_files += 1;
+                result.freed_bytes += size;
+                removed.insert(path);
+            }
+        }
+
+        // Links removed since could still point at them from the history
+        history.apply_to_all(|link_groups| {
+            forget_snapshots(link_groups, |link| {
+                link.snapshot
+                    .as_ref()
+                    .is_some_and(|snapshot| removed.contains(&PathBuf::from(&snapshot.path)))
+            })
+        });
+    }
+
+    Ok(result)
+}
@@ -1,65 +1,428 @@
+use std::collections::BTreeSet;
+
 use tauri::State;
 
-use crate::types::{LinksContainer, TagsContainer};
+use crate::{
+    tag_tree::{self, TagIssues},
+    types::{
+        is_valid_color, CanonicalizeContainer, HistoryContainer, LinkGroups, LinksContainer,
+        TagInfo, Tags, TagsContainer,
+    },
+};
+
+#[tauri::command]
+pub fn add_tag(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    name: String,
+) -> Result<(), String> {
+    let name = tag_tree::normalize(&name)?;
+
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
 
+    let name = tag_tree::resolve(&tags, &name);
+
+    let recording = history.record("Add tag", &link_groups, &tags);
+
+    tags.entry(name).or_default();
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
+
+// Removes the tag along with every tag underneath it, from the tag list and from
+// every link using them
 #[tauri::command]
-pub fn add_tag(tags: State<TagsContainer>, name: String) -> Result<(), String> {
-    let lock = tags.lock().map_err(|e| e.to_string())?;
-    let mut tags = lock.borrow_mut();
+pub fn remove_tag(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    name: String,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Remove tag", &link_groups, &tags);
+
+    for link in link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+        .filter(|link| link.tags.iter().any(|tag| tag_tree::is_within(tag, &name)))
+    {
+        link.tags.retain(|tag| !tag_tree::is_within(tag, &name));
+        link.touch();
+    }
+
+    tags.retain(|tag, _| !tag_tree::is_within(tag, &name));
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
+
+// Moves every tag within `from` to within `to`, on links and in the tag list,
+// returning whether anything was using `from`
+fn move_tags(link_groups: &mut LinkGroups, tags: &mut Tags, from: &str, to: &str) -> bool {
+    let mut found = false;
+
+    for link in link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+    {
+        if tag_tree::rewrite_tags(&mut link.tags, |tag| tag_tree::move_within(tag, from, to)) {
+            link.touch();
+            found = true;
+        }
+    }
+
+    let moved = tags
+        .keys()
+        .filter_map(|tag| tag_tree::move_within(tag, from, to).map(|moved| (tag.to_owned(), moved)))
+        .collect::<Vec<_>>();
+
+    found |= !moved.is_empty();
 
-    tags.insert(name);
+    // Metadata follows the tag, and is combined with any tag already at the new name
+    for (tag, new_tag) in moved {
+        if let Some(info) = tags.remove(&tag) {
+            tags.entry(new_tag).or_default().merge(info);
+        }
+    }
+
+    found
+}
+
+// Moving a tag underneath itself would leave its links pointing at a name that's
+// been moved away again, as `dev` to `dev/rust` also turns `dev/rust` into `dev/rust/rust`
+fn check_not_within(from: &str, to: &str) -> Result<(), String> {
+    if tag_tree::is_within(to, from) {
+        return Err(format!("Tag '{}' can't be moved into '{}'", from, to));
+    }
 
     Ok(())
 }
 
+// Renaming onto an existing tag merges the two. Tags underneath are renamed along
+// with it, so `dev` to `code` turns `dev/rust` into `code/rust`
 #[tauri::command]
-pub fn remove_tag(tags: State<TagsContainer>, name: String) -> Result<(), String> {
-    let lock = tags.lock().map_err(|e| e.to_string())?;
-    let mut tags = lock.borrow_mut();
+pub fn rename_tag(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    from: String,
+    to: String,
+) -> Result<(), String> {
+    let to = tag_tree::normalize(&to)?;
+
+    if from == to {
+        return Ok(());
+    }
+
+    check_not_within(&from, &to)?;
+
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Rename tag", &link_groups, &tags);
+
+    if !move_tags(&mut link_groups, &mut tags, &from, &to) {
+        return Err(format!("Tag '{}' wasn't found", from));
+    }
 
-    tags.remove(&name);
+    history.finish(recording, &link_groups, &tags);
 
     Ok(())
 }
 
+#[tauri::command]
+pub fn merge_tags(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    sources: Vec<String>,
+    target: String,
+) -> Result<(), String> {
+    let target = tag_tree::normalize(&target)?;
+
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let mut link_groups = lock.borrow_mut();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    // Checked up front so that a missing source leaves everything untouched
+    let missing = sources.iter().find(|source| {
+        !tags.contains_key(*source)
+            && !link_groups
+                .iter()
+                .flat_map(|group| &group.links)
+                .any(|link| link.tags.contains(source))
+    });
+
+    if let Some(missing) = missing {
+        return Err(format!("Tag '{}' wasn't found", missing));
+    }
+
+    for source in sources.iter().filter(|source| **source != target) {
+        check_not_within(source, &target)?;
+    }
+
+    let recording = history.record("Merge tags", &link_groups, &tags);
+
+    for source in sources.iter().filter(|source| **source != target) {
+        move_tags(&mut link_groups, &mut tags, source, &target);
+    }
+
+    tags.entry(target).or_default();
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_tag_info(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    name: String,
+    info: TagInfo,
+) -> Result<(), String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    if !tags.contains_key(&name) {
+        return Err(format!("Tag '{}' wasn't found", name));
+    }
+
+    if let Some(ref color) = info.color {
+        if !is_valid_color(color) {
+            return Err(format!("Invalid color: '{}'", color));
+        }
+    }
+
+    let mut aliases = Vec::<String>::with_capacity(info.aliases.len());
+
+    for alias in info.aliases.iter() {
+        let alias = tag_tree::normalize(alias)?;
+
+        if alias == name || tags.contains_key(&alias) {
+            return Err(format!("Alias '{}' is already a tag", alias));
+        }
+
+        let owner = tags
+            .iter()
+            .find(|(tag, info)| **tag != name && info.aliases.contains(&alias));
+
+        if let Some((owner, _)) = owner {
+            return Err(format!("Alias '{}' is already used by '{}'", alias, owner));
+        }
+
+        if !aliases.contains(&alias) {
+            aliases.push(alias);
+        }
+    }
+
+    // Blank fields are cleared rather than stored
+    let non_empty = |value: Option<String>| value.filter(|value| !value.trim().is_empty());
+
+    let recording = history.record("Edit tag", &link_groups, &tags);
+
+    tags.insert(
+        name,
+        TagInfo {
+            color: non_empty(info.color),
+            description: non_empty(info.description),
+            icon: non_empty(info.icon),
+            aliases,
+        },
+    );
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn reconcile_tags(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+    history: State<HistoryContainer>,
+    remove_unused: bool,
+) -> Result<TagIssues, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let recording = history.record("Tidy tags", &link_groups, &tags);
+
+    let issues = TagIssues::find(&link_groups, &tags).reconcile(&mut tags, remove_unused);
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(issues)
+}
+
+// Adds the tag to the link, or removes it when the link already has it, returning
+// the link's new tags. With `all_copies` every link sharing a canonical URI with it,
+// through its own URI or an alias, gets the same change
 #[tauri::command]
 pub fn toggle_tag(
     links: State<LinksContainer>,
-    link_uri: String,
+    tags: State<TagsContainer>,
+    canonicalize: State<CanonicalizeContainer>,
+    history: State<HistoryContainer>,
+    link_id: usize,
     tag_name: String,
-) -> Result<(), String> {
+    all_copies: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let tag_name = tag_tree::normalize(&tag_name)?;
+
     let lock = links.lock().map_err(|e| e.to_string())?;
     let mut link_groups = lock.borrow_mut();
 
-    let matched = link_groups
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let mut tags = tags_lock.borrow_mut();
+
+    let tag_name = tag_tree::resolve(&tags, &tag_name);
+
+    let canonicalize_lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = canonicalize_lock.borrow();
+
+    let history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut history = history_lock.borrow_mut();
+
+    let link = link_groups
         .iter()
-        .enumerate()
-        .flat_map(|(group_id, group)| {
-            group
-                .links
-                .iter()
-                .enumerate()
-                .map(|(link_id, link)| (group_id, link_id, link.clone()))
-                .collect::<Vec<_>>()
-                .to_owned()
+        .flat_map(|group| &group.links)
+        .find(|link| link.id == link_id)
+        .ok_or(format!("Link {} wasn't found", link_id))?;
+
+    let keys = link
+        .uris()
+        .map(|uri| canonicalize_config.canonicalize(uri))
+        .collect::<BTreeSet<_>>();
+    let adding = !link.tags.contains(&tag_name);
+
+    let mut new_tags = Vec::<String>::new();
+
+    let recording = history.record("Toggle tag", &link_groups, &tags);
+
+    for link in link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+        .filter(|link| {
+            link.id == link_id
+                || (all_copies.unwrap_or_default()
+                    && link
+                        .uris()
+                        .any(|uri| keys.contains(&canonicalize_config.canonicalize(uri))))
         })
-        .find(|(_, _, link)| link.uri == link_uri);
+    {
+        if adding {
+            if !link.tags.contains(&tag_name) {
+                link.tags.push(tag_name.to_owned());
+            }
+        } else {
+            link.tags.retain(|tag| tag != &tag_name);
+        }
 
-    if let Some((group_id, link_id, _)) = matched {
-        link_groups
-            .get_mut(group_id)
-            .and_then(|group| group.links.get_mut(link_id))
-            .map(|link| {
-                let indx = link.tags.iter().position(|tag| tag == &tag_name);
+        link.touch();
 
-                if let Some(i) = indx {
-                    link.tags.remove(i);
-                } else {
-                    link.tags.push(tag_name);
-                }
-            })
-            .expect("Passed ids not valid");
+        if link.id == link_id {
+            new_tags = link.tags.to_owned();
+        }
     }
 
-    Ok(())
+    if adding {
+        tags.entry(tag_name).or_default();
+    }
+
+    history.finish(recording, &link_groups, &tags);
+
+    Ok(new_tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Link, LinkGroup};
+
+    #[test]
+    fn tags_cant_move_within_themselves() {
+        assert!(check_not_within("dev", "dev/rust").is_err());
+        assert!(check_not_within("dev", "dev").is_err());
+        assert!(check_not_within("dev", "devops").is_ok());
+        assert!(check_not_within("dev/rust", "dev").is_ok());
+    }
+
+    #[test]
+    fn moving_a_tag_takes_its_children_and_info_along() {
+        let link = Link::new(
+            "https://example.com".to_string(),
+            "Example".to_string(),
+            vec!["dev/rust".to_string(), "devops".to_string()],
+        );
+        let mut link_groups = LinkGroups::from([LinkGroup::new([link].into())]);
+
+        let mut tags = Tags::new();
+
+        tags.insert(
+            "dev".to_string(),
+            TagInfo {
+                description: Some("Development".to_string()),
+                ..TagInfo::default()
+            },
+        );
+        tags.insert("devops".to_string(), TagInfo::default());
+
+        assert!(move_tags(&mut link_groups, &mut tags, "dev", "code"));
+
+        let link = &link_groups[0].links[0];
+
+        assert_eq!(link.tags, vec!["code/rust", "devops"]);
+        assert!(link.updated_at.is_some());
+        assert_eq!(
+            tags.keys().cloned().collect::<Vec<_>>(),
+            vec!["code", "devops"]
+        );
+        assert_eq!(tags["code"].description.as_deref(), Some("Development"));
+
+        assert!(!move_tags(&mut link_groups, &mut tags, "dev", "code"));
+    }
 }
@@ -0,0 +1,200 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+    http::{Request, Response, ResponseBuilder},
+    AppHandle,
+};
+use ureq::Agent;
+use url::Url;
+
+use crate::{
+    html,
+    net::{self, Fetched},
+    types::now,
+};
+
+pub const JOB_NAME: &str = "favicon_fetch";
+
+pub const PROTOCOL: &str = "icon";
+
+pub const MAX_REDIRECTS: u32 = 5;
+
+const CACHE_DIR: &str = "favicons";
+
+// Thirty days, hosts without an icon are only retried after this as well
+const EXPIRY_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+
+const MAX_PAGE_BYTES: u64 = 512 * 1024;
+const MAX_ICON_BYTES: u64 = 512 * 1024;
+
+// Stored next to the icon itself, with no icon file when the host didn't have one
+#[derive(Deserialize, Serialize)]
+struct CacheEntry {
+    content_type: Option<String>,
+    fetched_at: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct FaviconResult {
+    host: String,
+    found: bool,
+    error: Option<String>,
+}
+
+pub fn cache_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path_resolver()
+        .app_data_dir()
+        .ok_or("Unable to find the app data directory")?
+        .join(CACHE_DIR);
+
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    Ok(dir)
+}
+
+// Hosts end up in file names, so anything that isn't a plain hostname is refused
+pub fn is_valid_host(host: &str) -> bool {
+    !host.is_empty()
+        && !host.starts_with('.')
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+}
+
+fn entry_path(dir: &Path, host: &str) -> PathBuf {
+    dir.join(format!("{}.json", host))
+}
+
+fn icon_path(dir: &Path, host: &str) -> PathBuf {
+    dir.join(format!("{}.icon", host))
+}
+
+fn read_entry(dir: &Path, host: &str) -> Option<CacheEntry> {
+    let entry = fs::read_to_string(entry_path(dir, host)).ok()?;
+
+    serde_json::from_str(&entry).ok()
+}
+
+pub fn is_fresh(dir: &Path, host: &str) -> bool {
+    read_entry(dir, host).is_some_and(|entry| now().saturating_sub(entry.fetched_at) < EXPIRY_MS)
+}
+
+fn is_icon(fetched: &Fetched) -> bool {
+    !fetched.body.is_empty()
+        && (fetched.content_type.starts_with("image/")
+            // Plenty of servers send .ico files without a proper type
+            || fetched.content_type == "application/octet-stream")
+}
+
+// Icons declared by the home page win over `/favicon.ico`, with plain `icon`
+// declarations preferred over touch icons that tend to be much larger
+fn resolve(agent: &Agent, origin: &Url) -> Result<Option<Fetched>, String> {
+    let mut candidates = Vec::new();
+
+    if let Ok(page) = net::fetch_html(agent, origin.as_str(), MAX_PAGE_BYTES) {
+        let mut declared = html::find_tags(html::head(&page), "link")
+            .into_iter()
+            .filter_map(|link| {
+                let rel = link.get("rel")?.to_ascii_lowercase();
+                let rank = rel
+                    .split_whitespace()
+                    .filter_map(|rel| match rel {
+                        "icon" => Some(0),
+                        "apple-touch-icon" => Some(1),
+                        _ => None,
+                    })
+                    .min()?;
+
+                Some((rank, origin.join(link.get("href")?).ok()?))
+            })
+            .collect::<Vec<_>>();
+
+        declared.sort_by_key(|(rank, _)| *rank);
+        candidates.extend(declared.into_iter().map(|(_, url)| url));
+    }
+
+    candidates.push(origin.join("/favicon.ico").map_err(|e| e.to_string())?);
+
+    Ok(candidates.into_iter().find_map(|candidate| {
+        net::fetch(agent, candidate.as_str(), MAX_ICON_BYTES)
+            .ok()
+            .filter(is_icon)
+    }))
+}
+
+pub fn fetch_and_store(agent: &Agent, dir: &Path, host: &str, scheme: &str) -> FaviconResult {
+    let stored = Url::parse(&format!("{}://{}/", scheme, host))
+        .map_err(|e| e.to_string())
+        .and_then(|origin| resolve(agent, &origin))
+        .and_then(|icon| {
+            let entry = CacheEntry {
+                content_type: icon.as_ref().map(|icon| icon.content_type.to_owned()),
+                fetched_at: now(),
+            };
+
+            match icon {
+                Some(ref icon) => fs::write(icon_path(dir, host), &icon.body),
+                None => fs::remove_file(icon_path(dir, host)).or(Ok(())),
+            }
+            .map_err(|e| e.to_string())?;
+
+            let entry = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+
+            fs::write(entry_path(dir, host), entry).map_err(|e| e.to_string())?;
+
+            Ok(icon.is_some())
+        });
+
+    match stored {
+        Ok(found) => FaviconResult {
+            host: host.to_string(),
+            found,
+            error: None,
+        },
+        Err(error) => FaviconResult {
+            host: host.to_string(),
+            found: false,
+            error: Some(error),
+        },
+    }
+}
+
+// Webviews on Windows see custom protocols as `https://icon.localhost/<host>`
+// rather than `icon://<host>`, so the host is taken from the path there
+fn requested_host(uri: &str) -> Option<String> {
+    let url = Url::parse(uri).ok()?;
+
+    let host = match url.host_str()? {
+        "localhost" | "icon.localhost" => url.path_segments()?.next()?.to_string(),
+        host => host.to_string(),
+    };
+
+    Some(host.to_ascii_lowercase()).filter(|host| is_valid_host(host))
+}
+
+// Serves icons from the cache only, never touching the network, so that
+// rendering a list of links stays fast and works offline
+pub fn handle_protocol(app: &AppHandle, request: &Request) -> Result<Response, Box<dyn Error>> {
+    let icon = requested_host(request.uri()).and_then(|host| {
+        let dir = cache_dir(app).ok()?;
+        let entry = read_entry(&dir, &host)?;
+        let body = fs::read(icon_path(&dir, &host)).ok()?;
+
+        Some((entry.content_type?, body))
+    });
+
+    match icon {
+        Some((content_type, body)) => ResponseBuilder::new()
+            .status(200)
+            .mimetype(&content_type)
+            .header("Cache-Control", "max-age=86400")
+            .body(body),
+        None => ResponseBuilder::new().status(404).body(Vec::new()),
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{get_new_id, LinkGroups, SaveData};
+
+// Folders are kept in one list, where the order of folders sharing a parent is the
+// order they're shown in
+#[derive(Serialize, Clone, Debug)]
+pub struct Folder {
+    pub id: usize,
+    pub name: String,
+    pub parent_id: Option<usize>,
+}
+
+// Parents are stored as positions in the saved folder list, as folder ids aren't kept
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct StorableFolder {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<usize>,
+}
+
+pub fn find(folders: &[Folder], id: usize) -> Result<&Folder, String> {
+    folders
+        .iter()
+        .find(|folder| folder.id == id)
+        .ok_or(format!("Folder {} wasn't found", id))
+}
+
+pub fn validate_name(name: &str) -> Result<String, String> {
+    match name.trim() {
+        "" => Err("Folder name can't be empty".to_string()),
+        name => Ok(name.to_string()),
+    }
+}
+
+// Whether `id` is `ancestor` itself or anywhere underneath it
+pub fn is_within(folders: &[Folder], id: usize, ancestor: usize) -> bool {
+    let mut current = Some(id);
+
+    // Bounded so that a corrupted save with a cycle can't hang the app
+    for _ in 0..=folders.len() {
+        let Some(id) = current else {
+            break;
+        };
+
+        if id == ancestor {
+            return true;
+        }
+
+        current = folders
+            .iter()
+            .find(|folder| folder.id == id)
+            .and_then(|folder| folder.parent_id);
+    }
+
+    false
+}
+
+// The folder id if it's still around, so that anything filed under a removed or
+// missing folder is treated as top level
+pub fn existing(folders: &[Folder], id: Option<usize>) -> Option<usize> {
+    id.filter(|id| folders.iter().any(|folder| folder.id == *id))
+}
+
+pub fn children(folders: &[Folder], parent_id: Option<usize>) -> impl Iterator<Item = &Folder> {
+    folders
+        .iter()
+        .filter(move |folder| existing(folders, folder.parent_id) == parent_id)
+}
+
+pub fn to_storable(folders: &[Folder]) -> Vec<StorableFolder> {
+    folders
+        .iter()
+        .map(|folder| StorableFolder {
+            name: folder.name.to_owned(),
+            parent: folder
+                .parent_id
+                .and_then(|parent_id| folders.iter().position(|folder| folder.id == parent_id)),
+        })
+        .collect()
+}
+
+// Fills in the folder positions of groups already converted for saving
+pub fn store_group_folders(folders: &[Folder], link_groups: &LinkGroups, save_data: &mut SaveData) {
+    for (group, storable) in link_groups.iter().zip(save_data.link_groups.iter_mut()) {
+        storable.folder = group
+            .folder_id
+            .and_then(|folder_id| folders.iter().position(|folder| folder.id == folder_id));
+    }
+}
+
+// Adds the saved folders to `folders` under new ids, returning the id each saved
+// position ended up with
+pub fn load(stored: &[StorableFolder], folders: &mut Vec<Folder>) -> BTreeMap<usize, usize> {
+    let ids = stored
+        .iter()
+        .enumerate()
+        .map(|(position, _)| (position, get_new_id()))
+        .collect::<BTreeMap<_, _>>();
+
+    for (position, folder) in stored.iter().enumerate() {
+        folders.push(Folder {
+            id: ids[&position],
+            name: folder.name.to_owned(),
+            parent_id: folder.parent.and_then(|parent| ids.get(&parent).copied()),
+        });
+    }
+
+    ids
+}
@@ -0,0 +1,180 @@
+use serde::Serialize;
+use tauri::AppHandle;
+use ureq::Agent;
+use url::Url;
+
+use crate::{
+    jobs, net,
+    types::{now, LinkHealth},
+};
+
+pub const JOB_NAME: &str = "link_check";
+
+pub const MAX_REDIRECTS: u32 = 10;
+
+#[derive(Serialize, Clone)]
+pub struct CheckResult {
+    link_id: usize,
+    health: LinkHealth,
+}
+
+pub fn check_link(agent: &Agent, uri: &str) -> LinkHealth {
+    // The final URI comes back normalized, so it's compared to the normalized
+    // original to only report actual redirects
+    let requested = Url::parse(uri).map_or(uri.to_string(), String::from);
+
+    match net::head_or_get(agent, uri) {
+        Ok(response) => LinkHealth {
+            status: Some(response.status()),
+            final_uri: Some(response.get_url().to_string())
+                .filter(|final_uri| *final_uri != requested),
+            error: None,
+            checked_at: now(),
+        },
+        Err(error) => LinkHealth {
+            status: None,
+            final_uri: None,
+            error: Some(error),
+            checked_at: now(),
+        },
+    }
+}
+
+pub fn record_health(app: &AppHandle, link_id: usize, health: LinkHealth) -> CheckResult {
+    jobs::update_link(app, link_id, |link| link.health = Some(health.clone()));
+
+    CheckResult { link_id, health }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, time::Duration};
+
+    use super::*;
+    use crate::test_server::{Response, TestServer};
+
+    fn agent() -> Agent {
+        net::build_agent(Duration::from_secs(5), MAX_REDIRECTS)
+    }
+
+    fn check_status(status: u16) -> LinkHealth {
+        let server = TestServer::start(move |_| Response::new(status));
+
+        check_link(&agent(), &server.url("/page"))
+    }
+
+    #[test]
+    fn successful_pages_are_alive() {
+        let health = check_status(200);
+
+        assert_eq!(health.status, Some(200));
+        assert_eq!(health.final_uri, None);
+        assert!(!health.is_dead());
+    }
+
+    #[test]
+    fn normalized_uris_arent_reported_as_redirects() {
+        let server = TestServer::start(|_| Response::new(200));
+
+        // Requested as `.../` once normalized
+        let health = check_link(&agent(), &server.url(""));
+
+        assert_eq!(health.status, Some(200));
+        assert_eq!(health.final_uri, None);
+    }
+
+    #[test]
+    fn redirects_are_followed_and_reported() {
+        let server = TestServer::start(|request| match request.path.as_str() {
+            "/old" => Response::redirect(301, "/new"),
+            _ => Response::new(200),
+        });
+
+        let health = check_link(&agent(), &server.url("/old"));
+
+        assert_eq!(health.status, Some(200));
+        assert_eq!(health.final_uri, Some(server.url("/new")));
+        assert!(!health.is_dead());
+    }
+
+    #[test]
+    fn redirects_that_arent_followed_are_alive() {
+        let server = TestServer::start(|_| Response::redirect(302, "/elsewhere"));
+
+        let health = check_link(
+            &net::build_agent(Duration::from_secs(5), 0),
+            &server.url("/"),
+        );
+
+        assert_eq!(health.status, Some(302));
+        assert!(!health.is_dead());
+    }
+
+    #[test]
+    fn missing_pages_are_dead() {
+        assert!(check_status(404).is_dead());
+        assert!(check_status(410).is_dead());
+    }
+
+    #[test]
+    fn blocked_pages_arent_dead() {
+        assert!(!check_status(401).is_dead());
+        assert!(!check_status(403).is_dead());
+        assert!(!check_status(429).is_dead());
+    }
+
+    #[test]
+    fn server_errors_are_dead() {
+        let health = check_status(503);
+
+        assert_eq!(health.status, Some(503));
+        assert!(health.is_dead());
+    }
+
+    #[test]
+    fn unreachable_hosts_are_dead() {
+        // Nothing listens on the port once the listener is gone
+        let address = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .unwrap();
+
+        let health = check_link(&agent(), &format!("http://{}/", address));
+
+        assert_eq!(health.status, None);
+        assert!(health.error.is_some());
+        assert!(health.is_dead());
+    }
+
+    #[test]
+    fn head_is_retried_as_get_when_unsupported() {
+        let server = TestServer::start(|request| match request.method.as_str() {
+            "HEAD" => Response::new(405),
+            _ => Response::new(200),
+        });
+
+        let health = check_link(&agent(), &server.url("/page"));
+
+        assert_eq!(health.status, Some(200));
+
+        let methods = server
+            .requests()
+            .into_iter()
+            .map(|request| request.method)
+            .collect::<Vec<_>>();
+
+        assert_eq!(methods, ["HEAD", "GET"]);
+    }
+
+    #[test]
+    fn other_head_errors_are_kept() {
+        let server = TestServer::start(|request| match request.method.as_str() {
+            "HEAD" => Response::new(404),
+            _ => Response::new(200),
+        });
+
+        let health = check_link(&agent(), &server.url("/page"));
+
+        assert_eq!(health.status, Some(404));
+        assert_eq!(server.requests().len(), 1);
+    }
+}
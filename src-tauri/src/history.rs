@@ -0,0 +1,332 @@
+use std::collections::VecDeque;
+
+use crate::types::{LinkGroups, Tags};
+
+// Each entry holds a full copy of the library, so only the most recent are kept
+const MAX_ENTRIES: usize = 20;
+
+// The library as it was before an operation, put back by undoing it
+pub struct HistoryEntry {
+    pub label: String,
+    pub link_groups: LinkGroups,
+    pub tags: Tags,
+}
+
+// Started by `History::record` before an operation changes anything, and only kept
+// once passed to `History::finish`, so commands failing part way through leave
+// nothing behind by dropping it
+#[must_use]
+pub struct Recording {
+    label: String,
+    link_groups: LinkGroups,
+    tags: Tags,
+}
+
+// Every command changing links or tags records itself, while changes that aren't
+// undoable, such as job results, go through `apply_to_all`. Each entry's library is
+// how the one before it left things, so only the latest state after an operation
+// is kept, to notice changes made since
+#[derive(Default)]
+pub struct History {
+    entries: VecDeque<HistoryEntry>,
+    after: Option<(LinkGroups, Tags)>,
+}
+
+impl History {
+    // Called before an operation changes anything
+    pub fn record(&self, label: &str, link_groups: &LinkGroups, tags: &Tags) -> Recording {
+        Recording {
+            label: label.to_string(),
+            link_groups: link_groups.clone(),
+            tags: tags.clone(),
+        }
+    }
+
+    // Called once the operation started by `recording` has made its changes
+    pub fn finish(&mut self, recording: Recording, link_groups: &LinkGroups, tags: &Tags) {
+        // Nothing to undo when the operation didn't change anything
+        if recording.link_groups == *link_groups && recording.tags == *tags {
+            return;
+        }
+
+        // Undoing past a change that wasn't recorded would lose it, so the entries
+        // from before it are dropped
+        if self
+            .after
+            .as_ref()
+            .is_some_and(|(after_link_groups, after_tags)| {
+                *after_link_groups != recording.link_groups || *after_tags != recording.tags
+            })
+        {
+            self.entries.clear();
+        }
+
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(HistoryEntry {
+            label: recording.label,
+            link_groups: recording.link_groups,
+            tags: recording.tags,
+        });
+
+        self.after = Some((link_groups.clone(), tags.clone()));
+    }
+
+    // Takes the last entry, as long as the library is still as that operation left it
+    pub fn pop(&mut self, link_groups: &LinkGroups, tags: &Tags) -> Result<HistoryEntry, String> {
+        let entry = self.entries.pop_back().ok_or("Nothing to undo")?;
+
+        if self
+            .after
+            .as_ref()
+            .is_some_and(|(after_link_groups, after_tags)| {
+                after_link_groups != link_groups || after_tags != tags
+            })
+        {
+            let error = format!(
+                "The library has changed since '{}', so it can't be undone",
+                entry.label
+            );
+
+            self.entries.push_back(entry);
+
+            return Err(error);
+        }
+
+        // The library is about to be put back as the previous operation left it
+        self.after = Some((entry.link_groups.clone(), entry.tags.clone()));
+
+        Ok(entry)
+    }
+
+    // Applies changes that aren't undoable, such as when links were last opened, to
+    // every copy of the library so they don't block undoing or get rolled back
+    pub fn apply_to_all<F>(&mut self, mut apply: F)
+    where
+        F: FnMut(&mut LinkGroups),
+    {
+        for entry in self.entries.iter_mut() {
+            apply(&mut entry.link_groups);
+        }
+
+        if let Some((link_groups, _)) = self.after.as_mut() {
+            apply(link_groups);
+        }
+    }
+
+    // Labels of the operations that can be undone, most recent first
+    pub fn labels(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .rev()
+            .map(|entry| entry.label.to_owned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Link, LinkGroup};
+
+    fn library(uris: &[&str]) -> LinkGroups {
+        let links = uris
+            .iter()
+            .map(|uri| Link::new(uri.to_string(), uri.to_string(), Vec::new()))
+            .collect();
+
+        LinkGroups::from([LinkGroup::new(links)])
+    }
+
+    // Runs `change` as a recorded operation
+    fn run<F>(
+        history: &mut History,
+        label: &str,
+        link_groups: &mut LinkGroups,
+        tags: &Tags,
+        change: F,
+    ) where
+        F: FnOnce(&mut LinkGroups),
+    {
+        let recording = history.record(label, link_groups, tags);
+
+        change(link_groups);
+
+        history.finish(recording, link_groups, tags);
+    }
+
+    fn remove_first(link_groups: &mut LinkGroups) {
+        link_groups[0].links.pop_front();
+    }
+
+    #[test]
+    fn undoing_puts_back_the_library_from_before() {
+        let mut history = History::default();
+        let mut link_groups = library(&["https://a.com", "https://b.com", "https://c.com"]);
+        let tags = Tags::new();
+        let original = link_groups.clone();
+
+        run(&mut history, "First", &mut link_groups, &tags, remove_first);
+        let after_first = link_groups.clone();
+        run(
+            &mut history,
+            "Second",
+            &mut link_groups,
+            &tags,
+            remove_first,
+        );
+
+        assert_eq!(history.labels(), vec!["Second", "First"]);
+
+        let entry = history.pop(&link_groups, &tags).unwrap();
+
+        assert_eq!(entry.label, "Second");
+        assert!(entry.link_groups == after_first);
+
+        link_groups = entry.link_groups;
+
+        let entry = history.pop(&link_groups, &tags).unwrap();
+
+        assert!(entry.link_groups == original);
+        assert_eq!(
+            history.pop(&entry.link_groups, &tags).err().as_deref(),
+            Some("Nothing to undo")
+        );
+    }
+
+    #[test]
+    fn operations_without_changes_or_finish_leave_nothing() {
+        let mut history = History::default();
+        let mut link_groups = library(&["https://a.com", "https://b.com"]);
+        let tags = Tags::new();
+
+        run(&mut history, "Nothing", &mut link_groups, &tags, |_| {});
+
+        // A command failing after recording drops its recording
+        let recording = history.record("Failed", &link_groups, &tags);
+        drop(recording);
+
+        assert!(history.labels().is_empty());
+
+        run(
+            &mut history,
+            "Remove",
+            &mut link_groups,
+            &tags,
+            remove_first,
+        );
+
+        assert_eq!(history.labels(), vec!["Remove"]);
+    }
+
+    #[test]
+    fn undoing_is_refused_after_unrecorded_changes() {
+        let mut history = History::default();
+        let mut link_groups = library(&["https://a.com", "https://b.com"]);
+        let tags = Tags::new();
+
+        run(
+            &mut history,
+            "Remove",
+            &mut link_groups,
+            &tags,
+            remove_first,
+        );
+
+        link_groups[0].links[0].title = "Changed".to_string();
+
+        assert_eq!(
+            history.pop(&link_groups, &tags).err().as_deref(),
+            Some("The library has changed since 'Remove', so it can't be undone")
+        );
+        // The entry is kept for once the library is back as it was
+        assert_eq!(history.labels(), vec!["Remove"]);
+    }
+
+    #[test]
+    fn unrecorded_changes_drop_the_entries_before_them() {
+        let mut history = History::default();
+        let mut link_groups = library(&["https://a.com", "https://b.com", "https://c.com"]);
+        let tags = Tags::new();
+
+        run(&mut history, "First", &mut link_groups, &tags, remove_first);
+
+        link_groups[0].links[0].title = "Changed".to_string();
+
+        run(
+            &mut history,
+            "Second",
+            &mut link_groups,
+            &tags,
+            remove_first,
+        );
+
+        assert_eq!(history.labels(), vec!["Second"]);
+    }
+
+    #[test]
+    fn changes_applied_to_all_copies_dont_block_undoing() {
+        let mut history = History::default();
+        let mut link_groups = library(&["https://a.com", "https://b.com", "https://c.com"]);
+        let tags = Tags::new();
+
+        run(&mut history, "First", &mut link_groups, &tags, remove_first);
+        run(
+            &mut history,
+            "Second",
+            &mut link_groups,
+            &tags,
+            remove_first,
+        );
+
+        let open = |link_groups: &mut LinkGroups| {
+            for link in link_groups
+                .iter_mut()
+                .flat_map(|group| group.links.iter_mut())
+            {
+                link.last_opened_at = Some(1);
+            }
+        };
+
+        open(&mut link_groups);
+        history.apply_to_all(open);
+
+        let entry = history.pop(&link_groups, &tags).unwrap();
+        let entry = history.pop(&entry.link_groups, &tags).unwrap();
+
+        assert_eq!(entry.link_groups[0].links.len(), 3);
+        assert!(entry.link_groups[0]
+            .links
+            .iter()
+            .all(|link| link.last_opened_at == Some(1)));
+    }
+
+    #[test]
+    fn only_the_latest_entries_are_kept() {
+        let mut history = History::default();
+        let uris = (0..=MAX_ENTRIES)
+            .map(|i| format!("https://{}.com", i))
+            .collect::<Vec<_>>();
+        let mut link_groups = library(&uris.iter().map(String::as_str).collect::<Vec<_>>());
+        let tags = Tags::new();
+
+        for i in 0..=MAX_ENTRIES {
+            run(
+                &mut history,
+                &i.to_string(),
+                &mut link_groups,
+                &tags,
+                remove_first,
+            );
+        }
+
+        let labels = history.labels();
+
+        assert_eq!(labels.len(), MAX_ENTRIES);
+        assert_eq!(labels.first(), Some(&MAX_ENTRIES.to_string()));
+        // The very first operation is the one dropped
+        assert_eq!(labels.last().map(String::as_str), Some("1"));
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+
+// A deliberately small scanner for the handful of tags the app cares about. It
+// doesn't build a tree, so it only works for void or text-only elements
+
+pub type Attributes = BTreeMap<String, String>;
+
+// The part of the document before `</head>`, or all of it when there's no head
+pub fn head(html: &str) -> &str {
+    let end = html
+        .to_ascii_lowercase()
+        .find("</head")
+        .unwrap_or(html.len());
+
+    &html[..end]
+}
+
+fn is_tag_start(lowercase: &str, at: usize, tag_name: &str) -> bool {
+    let after = at + 1 + tag_name.len();
+
+    lowercase[at + 1..].starts_with(tag_name)
+        && lowercase[after..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_whitespace() || c == '>' || c == '/')
+}
+
+// Positions of every opening `<tag_name ...>`, as the span of the whole tag and
+// the span of its attributes
+fn tag_spans(html: &str, tag_name: &str) -> Vec<(usize, usize, usize)> {
+    // ASCII lowercasing keeps byte offsets the same as the original
+    let lowercase = html.to_ascii_lowercase();
+    let mut spans = Vec::new();
+    let mut from = 0;
+
+    while let Some(offset) = lowercase[from..].find('<') {
+        let start = from + offset;
+
+        if !is_tag_start(&lowercase, start, tag_name) {
+            from = start + 1;
+            continue;
+        }
+
+        let Some(end) = lowercase[start..].find('>').map(|end| start + end) else {
+            break;
+        };
+
+        spans.push((start, start + 1 + tag_name.len(), end));
+        from = end + 1;
+    }
+
+    spans
+}
+
+pub fn find_tags(html: &str, tag_name: &str) -> Vec<Attributes> {
+    tag_spans(html, tag_name)
+        .into_iter()
+        .map(|(_, attributes_start, end)| parse_attributes(&html[attributes_start..end]))
+        .collect()
+}
+
+// Rebuilds the document with every `<tag_name>` that `replace` returns something
+// for swapped out for that
+pub fn replace_tags<F>(html: &str, tag_name: &str, mut replace: F) -> String
+where
+    F: FnMut(&Attributes) -> Option<String>,
+{
+    let mut result = String::with_capacity(html.len());
+    let mut copied_up_to = 0;
+
+    for (start, attributes_start, end) in tag_spans(html, tag_name) {
+        if let Some(replacement) = replace(&parse_attributes(&html[attributes_start..end])) {
+            result.push_str(&html[copied_up_to..start]);
+            result.push_str(&replacement);
+            copied_up_to = end + 1;
+        }
+    }
+
+    result.push_str(&html[copied_up_to..]);
+
+    result
+}
+
+pub fn render_tag(tag_name: &str, attributes: &Attributes) -> String {
+    let mut tag = format!("<{}", tag_name);
+
+    for (name, value) in attributes {
+        tag.push_str(&format!(" {}=\"{}\"", name, escape_attribute(value)));
+    }
+
+    tag.push('>');
+
+    tag
+}
+
+pub fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+pub fn escape_text(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Inserts `content` at the start of the head, or of the document if it has none
+pub fn prepend_to_head(html: &str, content: &str) -> String {
+    let at = tag_spans(html, "head")
+        .first()
+        .map(|(_, _, end)| end + 1)
+        .unwrap_or_default();
+
+    format!("{}{}{}", &html[..at], content, &html[at..])
+}
+
+// Text content of the first `<tag_name>`, with entities decoded and whitespace collapsed
+pub fn text_of(html: &str, tag_name: &str) -> Option<String> {
+    let (_, _, open_end) = *tag_spans(html, tag_name).first()?;
+
+    let content_start = open_end + 1;
+    let content_end = html[content_start..]
+        .to_ascii_lowercase()
+        .find(&format!("</{}", tag_name))
+        .map(|end| content_start + end)?;
+
+    let text = decode_entities(&html[content_start..content_end])
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(text).filter(|text| !text.is_empty())
+}
+
+pub fn parse_attributes(source: &str) -> Attributes {
+    let mut attributes = Attributes::new();
+    let mut rest = source.trim_start_matches('/').trim();
+
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let value = if let Some(after_equals) = rest.strip_prefix('=') {
+            let after_equals = after_equals.trim_start();
+
+            let (value, remaining) = match after_equals.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &after_equals[1..];
+                    let end = inner.find(quote).unwrap_or(inner.len());
+
+                    (&inner[..end], inner.get(end + 1..).unwrap_or_default())
+                }
+                _ => {
+                    let end = after_equals
+                        .find(|c: char| c.is_ascii_whitespace())
+                        .unwrap_or(after_equals.len());
+
+                    (&after_equals[..end], &after_equals[end..])
+                }
+            };
+
+            rest = remaining.trim_start();
+
+            decode_entities(value)
+        } else {
+            String::new()
+        };
+
+        if !name.is_empty() {
+            attributes.entry(name).or_insert(value);
+        }
+
+        rest = rest.trim_start_matches('/').trim_start();
+    }
+
+    attributes
+}
+
+pub fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').filter(|end| *end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+
+            let char = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(|decimal| decimal.parse()))
+                    .and_then(|code| code.ok())
+                    .and_then(char::from_u32),
+            };
+
+            char.map(|char| (char, end))
+        });
+
+        match decoded {
+            Some((char, end)) => {
+                result.push(char);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+
+    result
+}
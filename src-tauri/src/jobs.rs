@@ -0,0 +1,151 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    net,
+    types::{HistoryContainer, JobsContainer, Link, LinkGroups, LinksContainer},
+};
+
+pub const PROGRESS_EVENT: &str = "job-progress";
+pub const FINISHED_EVENT: &str = "job-finished";
+
+#[derive(Serialize, Clone)]
+struct JobProgress<R> {
+    job: &'static str,
+    done: usize,
+    total: usize,
+    result: R,
+}
+
+#[derive(Serialize, Clone)]
+struct JobFinished {
+    job: &'static str,
+    done: usize,
+    total: usize,
+    cancelled: bool,
+}
+
+// Runs `work` over every item on a background thread pool, emitting a progress
+// event per item and a finished event at the end. Only one job per name can run
+// at a time, returns the number of items queued
+pub fn spawn<T, R, F>(
+    app: AppHandle,
+    jobs: &JobsContainer,
+    name: &'static str,
+    items: Vec<T>,
+    concurrency: usize,
+    work: F,
+) -> Result<usize, String>
+where
+    T: Send + 'static,
+    R: Serialize + Clone,
+    F: Fn(&AppHandle, T) -> R + Send + Sync + 'static,
+{
+    let cancelled = {
+        let lock = jobs.lock().map_err(|e| e.to_string())?;
+        let mut running = lock.borrow_mut();
+
+        if running.contains_key(name) {
+            return Err(format!("A {} job is already running", name));
+        }
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        running.insert(name.to_string(), cancelled.clone());
+
+        cancelled
+    };
+
+    let total = items.len();
+
+    thread::spawn(move || {
+        let done = AtomicUsize::new(0);
+
+        net::run_pool(items, concurrency, &cancelled, |item| {
+            let result = work(&app, item);
+            let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+
+            app.emit_all(
+                PROGRESS_EVENT,
+                JobProgress {
+                    job: name,
+                    done,
+                    total,
+                    result,
+                },
+            )
+            .ok();
+        });
+
+        if let Ok(lock) = app.state::<JobsContainer>().lock() {
+            lock.borrow_mut().remove(name);
+        }
+
+        app.emit_all(
+            FINISHED_EVENT,
+            JobFinished {
+                job: name,
+                done: done.into_inner(),
+                total,
+                cancelled: cancelled.load(Ordering::Relaxed),
+            },
+        )
+        .ok();
+    });
+
+    Ok(total)
+}
+
+pub fn cancel(jobs: &JobsContainer, name: &str) -> Result<(), String> {
+    let lock = jobs.lock().map_err(|e| e.to_string())?;
+    let running = lock.borrow();
+
+    if let Some(cancelled) = running.get(name) {
+        cancelled.store(true, Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+// Jobs write their results back as they come in so that progress survives a
+// cancellation. The link may have been removed while it was being worked on, in
+// which case this does nothing. Results aren't undoable, so they're written to
+// the history's copies of the link too. Returns what `update` returned for the
+// link in the library
+pub fn update_link<R, F>(app: &AppHandle, link_id: usize, mut update: F) -> Option<R>
+where
+    F: FnMut(&mut Link) -> R,
+{
+    let links = app.state::<LinksContainer>();
+    let history = app.state::<HistoryContainer>();
+
+    let lock = links.lock().ok()?;
+    let mut link_groups = lock.borrow_mut();
+
+    let history_lock = history.lock().ok()?;
+    let mut history = history_lock.borrow_mut();
+
+    let mut update_in = |link_groups: &mut LinkGroups| {
+        link_groups
+            .iter_mut()
+            .flat_map(|group| group.links.iter_mut())
+            .find(|link| link.id == link_id)
+            .map(&mut update)
+    };
+
+    let updated = update_in(&mut link_groups);
+
+    history.apply_to_all(|link_groups| {
+        update_in(link_groups);
+    });
+
+    updated
+}
@@ -1,50 +1,151 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod canonical;
 mod commands;
+mod favicons;
+mod folders;
+mod health;
+mod history;
+mod html;
+mod jobs;
+mod metadata;
+mod net;
 mod queries;
+mod redirects;
+mod rules;
+mod similarity;
+mod snapshots;
+mod tag_tree;
+#[cfg(test)]
+mod test_server;
 mod types;
 
-use types::{LinksContainer, TagsContainer};
+use types::{
+    CanonicalizeContainer, FoldersContainer, HistoryContainer, JobsContainer, LinksContainer,
+    TagRulesContainer, TagsContainer,
+};
 
 use commands::{
-    add_group, add_link, add_tag, check_for_duplicates, export_for_onetab, import_from_file,
-    purge_links, remove_empty_groups, remove_group, remove_link, remove_tag, reorder_group,
-    reorder_link, save_data, toggle_tag,
+    add_folder, add_group, add_link, add_tag, add_tag_rule, apply_layout, apply_tag_rules,
+    archive_links, bulk_edit_links, cancel_job, check_for_duplicates, check_links,
+    clear_favicon_cache, delete_snapshots, expand_redirects, export_as_chromium,
+    export_as_markdown, export_as_netscape, export_for_onetab, fetch_favicons, fetch_metadata,
+    find_and_replace, import_from_file, mark_group_opened, mark_link_opened, merge_groups,
+    merge_links, merge_tags, move_folder, move_group_to_folder, open_snapshot, propose_layout,
+    prune_snapshots, purge_links, reconcile_tags, remove_dead_links, remove_empty_groups,
+    remove_folder, remove_group, remove_link, remove_tag, remove_tag_rule, rename_folder,
+    rename_tag, reorder_group, reorder_link, replace_low_quality_titles, resolve_all_dupes,
+    save_data, set_group_flags, set_link_content, set_link_note, set_tag_info, set_tracking_params,
+    sort_group, split_group, tag_dead_links, toggle_tag, undo, update_group, update_link,
+    update_tag_rule,
 };
 
-use queries::{get_links, get_tags, get_tags_for_link, resolve_dupes, search};
+use queries::{
+    find_near_dupes, find_tag_issues, get_dead_links, get_folders, get_history, get_links,
+    get_snapshot_usage, get_tag_info, get_tag_rules, get_tag_stats, get_tag_tree, get_tags,
+    get_tags_for_link, get_tracking_params, resolve_dupes, search,
+};
 
 // Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 
 fn main() {
     let links = LinksContainer::default();
     let tags = TagsContainer::default();
+    let canonicalize = CanonicalizeContainer::default();
+    let jobs = JobsContainer::default();
+    let tag_rules = TagRulesContainer::default();
+    let history = HistoryContainer::default();
+    let folders = FoldersContainer::default();
 
     tauri::Builder::default()
         .manage(links)
         .manage(tags)
+        .manage(canonicalize)
+        .manage(jobs)
+        .manage(tag_rules)
+        .manage(history)
+        .manage(folders)
+        .register_uri_scheme_protocol(favicons::PROTOCOL, favicons::handle_protocol)
         .invoke_handler(tauri::generate_handler![
+            add_folder,
             add_group,
             add_link,
             add_tag,
+            add_tag_rule,
+            apply_layout,
+            apply_tag_rules,
+            archive_links,
+            bulk_edit_links,
+            cancel_job,
             check_for_duplicates,
+            check_links,
+            clear_favicon_cache,
+            delete_snapshots,
+            expand_redirects,
+            export_as_chromium,
+            export_as_markdown,
+            export_as_netscape,
             export_for_onetab,
+            fetch_favicons,
+            fetch_metadata,
+            find_and_replace,
+            find_near_dupes,
+            find_tag_issues,
+            get_dead_links,
+            get_folders,
+            get_history,
             get_links,
+            get_snapshot_usage,
+            get_tag_info,
+            get_tag_rules,
+            get_tag_stats,
+            get_tag_tree,
             get_tags_for_link,
             get_tags,
+            get_tracking_params,
             import_from_file,
+            mark_group_opened,
+            mark_link_opened,
+            merge_groups,
+            merge_links,
+            merge_tags,
+            move_folder,
+            move_group_to_folder,
+            open_snapshot,
+            propose_layout,
+            prune_snapshots,
             purge_links,
+            reconcile_tags,
+            remove_dead_links,
             remove_empty_groups,
+            remove_folder,
             remove_group,
             remove_link,
             remove_tag,
+            remove_tag_rule,
+            rename_folder,
+            rename_tag,
             reorder_group,
             reorder_link,
+            replace_low_quality_titles,
+            resolve_all_dupes,
             resolve_dupes,
             save_data,
             search,
+            set_group_flags,
+            set_link_content,
+            set_link_note,
+            set_tag_info,
+            set_tracking_params,
+            sort_group,
+            split_group,
+            tag_dead_links,
             toggle_tag,
+            undo,
+            update_group,
+            update_link,
+            update_tag_rule,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,411 @@
+use serde::Serialize;
+use tauri::AppHandle;
+use url::Url;
+
+use crate::{
+    html, jobs,
+    types::{now, Link, LinkGroups, PageContent},
+};
+
+pub const JOB_NAME: &str = "metadata_fetch";
+
+pub const MAX_REDIRECTS: u32 = 10;
+
+// Everything the fetcher looks for lives in the head, which is rarely this big
+pub const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+// Lowercased titles that browsers and OneTab save while a page is still loading
+// or when it failed to load
+const PLACEHOLDER_TITLES: [&str; 10] = [
+    "",
+    "loading",
+    "loading...",
+    "untitled",
+    "new tab",
+    "redirecting",
+    "redirecting...",
+    "just a moment...",
+    "404 not found",
+    "403 forbidden",
+];
+
+#[derive(Serialize, Clone)]
+pub struct FetchResult {
+    link_id: usize,
+    content: Option<PageContent>,
+    error: Option<String>,
+}
+
+pub fn parse(page: &str, page_uri: &str) -> PageContent {
+    let head = html::head(page);
+    let base = Url::parse(page_uri).ok();
+
+    let resolve = |href: &String| match base {
+        Some(ref base) => base.join(href).map(|url| url.to_string()).ok(),
+        None => Some(href.to_owned()),
+    };
+
+    let mut content = PageContent {
+        title: html::text_of(head, "title"),
+        fetched_at: Some(now()),
+        ..Default::default()
+    };
+
+    let mut meta_description = None;
+
+    for meta in html::find_tags(head, "meta") {
+        let (Some(key), Some(value)) = (
+            meta.get("property").or(meta.get("name")),
+            meta.get("content").filter(|value| !value.trim().is_empty()),
+        ) else {
+            continue;
+        };
+
+        let value = Some(value.trim().to_string());
+
+        match key.to_ascii_lowercase().as_str() {
+            "og:title" => content.og_title = content.og_title.or(value),
+            "og:description" => content.description = content.description.or(value),
+            "description" => meta_description = meta_description.or(value),
+            "og:image" => {
+                content.image_uri = content.image_uri.or(value.as_ref().and_then(resolve))
+            }
+            _ => {}
+        }
+    }
+
+    // OpenGraph descriptions are written for sharing, so they're preferred
+    content.description = content.description.or(meta_description);
+
+    content.canonical_uri = html::find_tags(head, "link")
+        .iter()
+        .find(|link| {
+            link.get("rel").is_some_and(|rel| {
+                rel.split_whitespace()
+                    .any(|rel| rel.eq_ignore_ascii_case("canonical"))
+            })
+        })
+        .and_then(|link| link.get("href"))
+        .and_then(resolve);
+
+    content
+}
+
+// Keeps any text that was cached separately, since the fetcher doesn't extract it
+pub fn record(
+    app: &AppHandle,
+    link_id: usize,
+    fetched: Result<PageContent, String>,
+) -> FetchResult {
+    match fetched {
+        Ok(content) => {
+            // Text is set by the user rather than fetched, so it's kept
+            jobs::update_link(app, link_id, |link| {
+                let text = link
+                    .content
+                    .as_ref()
+                    .and_then(|content| content.text.clone());
+
+                link.content = Some(PageContent {
+                    text,
+                    ..content.clone()
+                });
+            });
+
+            FetchResult {
+                link_id,
+                content: Some(content),
+                error: None,
+            }
+        }
+        Err(error) => FetchResult {
+            link_id,
+            content: None,
+            error: Some(error),
+        },
+    }
+}
+
+fn is_low_quality(title: &str, link: &Link) -> bool {
+    let title = title.trim();
+    let lowercase = title.to_lowercase();
+
+    // Bare domains count with or without `www.` on either side
+    let domain = link.domain().to_lowercase();
+    let without_www = |host: &str| host.strip_prefix("www.").unwrap_or(host).to_string();
+
+    PLACEHOLDER_TITLES.contains(&lowercase.as_str())
+        || title == link.uri
+        || lowercase.starts_with("http://")
+        || lowercase.starts_with("https://")
+        || without_www(&lowercase) == without_www(&domain)
+}
+
+pub fn is_low_quality_title(link: &Link) -> bool {
+    is_low_quality(&link.title, link)
+}
+
+// The fetched title that should replace a poor one, if there's a better one
+pub fn better_title(link: &Link) -> Option<String> {
+    let content = link.content.as_ref()?;
+
+    [&content.og_title, &content.title]
+        .into_iter()
+        .flatten()
+        .map(|title| title.trim().to_string())
+        .find(|title| !is_low_quality(title, link))
+}
+
+#[derive(Serialize)]
+pub struct TitleReplacement {
+    link_id: usize,
+    old_title: String,
+    new_title: String,
+}
+
+// Replaces poor titles with fetched ones where there's a better one, or with
+// `preview` only lists what would be replaced
+pub fn replace_low_quality_titles(
+    link_groups: &mut LinkGroups,
+    preview: bool,
+) -> Vec<TitleReplacement> {
+    let mut replacements = Vec::new();
+
+    for link in link_groups
+        .iter_mut()
+        .flat_map(|group| group.links.iter_mut())
+        .filter(|link| is_low_quality_title(link))
+    {
+        let Some(new_title) = better_title(link) else {
+            continue;
+        };
+
+        let old_title = link.title.to_owned();
+
+        if !preview {
+            link.title = new_title.to_owned();
+            link.touch();
+        }
+
+        replacements.push(TitleReplacement {
+            link_id: link.id,
+            old_title,
+            new_title,
+        });
+    }
+
+    replacements
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, time::Duration};
+
+    use super::*;
+    use crate::{
+        net,
+        test_server::{Response, TestServer},
+        types::LinkGroup,
+    };
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>  Rust &amp; WebAssembly  </title>
+    <meta name="description" content="Plain description">
+    <meta property="og:title" content="Rust and WebAssembly">
+    <meta property="og:description" content="Shared description">
+    <meta property="og:image" content="/images/cover.png">
+    <link rel="stylesheet" href="/style.css">
+    <link rel="alternate canonical" href="/book/">
+</head>
+<body><title>Not this one</title></body>
+</html>"#;
+
+    fn fetch_page(path: &str, response: fn() -> Response) -> Result<PageContent, String> {
+        let server = TestServer::start(move |_| response());
+        let agent = net::build_agent(Duration::from_secs(5), MAX_REDIRECTS);
+        let uri = server.url(path);
+
+        net::fetch_html(&agent, &uri, MAX_BODY_BYTES).map(|page| parse(&page, &uri))
+    }
+
+    fn link_with(title: &str, content: Option<PageContent>) -> Link {
+        let mut link = Link::new(
+            "https://www.example.com/post".to_string(),
+            title.to_string(),
+            Vec::new(),
+        );
+
+        link.content = content;
+
+        link
+    }
+
+    #[test]
+    fn extracts_metadata_from_served_pages() {
+        let content = fetch_page("/docs/page", || Response::html(PAGE)).unwrap();
+
+        assert_eq!(content.title.as_deref(), Some("Rust & WebAssembly"));
+        assert_eq!(content.og_title.as_deref(), Some("Rust and WebAssembly"));
+        assert_eq!(content.description.as_deref(), Some("Shared description"));
+        assert!(content.fetched_at.is_some());
+    }
+
+    #[test]
+    fn resolves_canonical_and_image_against_the_page() {
+        let content = fetch_page("/docs/page", || Response::html(PAGE)).unwrap();
+
+        let base = content
+            .canonical_uri
+            .as_deref()
+            .unwrap()
+            .replace("/book/", "");
+
+        assert!(base.starts_with("http://127.0.0.1:"));
+        assert_eq!(
+            content.image_uri,
+            Some(format!("{}/images/cover.png", base))
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_meta_description() {
+        let content = fetch_page("/", || {
+            Response::html(r#"<head><meta name="description" content=" Only this "></head>"#)
+        })
+        .unwrap();
+
+        assert_eq!(content.description.as_deref(), Some("Only this"));
+        assert_eq!(content.title, None);
+        assert_eq!(content.canonical_uri, None);
+    }
+
+    #[test]
+    fn rejects_pages_that_arent_html() {
+        let fetched = fetch_page("/data.json", || {
+            Response::new(200)
+                .header("Content-Type", "application/json")
+                .body("{}")
+        });
+
+        assert!(fetched.is_err_and(|error| error.starts_with("Not an HTML page")));
+    }
+
+    #[test]
+    fn rejects_error_pages() {
+        let fetched = fetch_page("/missing", || Response::new(404).body("<title>404</title>"));
+
+        assert_eq!(fetched, Err("Server responded with 404".to_string()));
+    }
+
+    #[test]
+    fn spots_low_quality_titles() {
+        for title in [
+            "",
+            "  Loading...  ",
+            "New Tab",
+            "https://www.example.com/post",
+            "http://example.com",
+            "www.example.com",
+            "example.com",
+        ] {
+            assert!(is_low_quality_title(&link_with(title, None)), "{:?}", title);
+        }
+
+        for title in [
+            "Example post",
+            "Loading times explained",
+            "example.com review",
+        ] {
+            assert!(
+                !is_low_quality_title(&link_with(title, None)),
+                "{:?}",
+                title
+            );
+        }
+    }
+
+    #[test]
+    fn prefers_the_opengraph_title_unless_its_poor() {
+        let content = PageContent {
+            title: Some("Page title".to_string()),
+            og_title: Some("Shared title".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            better_title(&link_with("", Some(content))).as_deref(),
+            Some("Shared title")
+        );
+
+        let content = PageContent {
+            title: Some(" Page title ".to_string()),
+            og_title: Some("Untitled".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            better_title(&link_with("", Some(content))).as_deref(),
+            Some("Page title")
+        );
+
+        let content = PageContent {
+            title: Some("Just a moment...".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(better_title(&link_with("", Some(content))), None);
+    }
+
+    fn library() -> LinkGroups {
+        let content = PageContent {
+            title: Some("Fetched title".to_string()),
+            ..Default::default()
+        };
+
+        VecDeque::from([LinkGroup::new(VecDeque::from([
+            link_with("Loading...", Some(content.clone())),
+            link_with("Kept title", Some(content)),
+            link_with("New Tab", None),
+        ]))])
+    }
+
+    fn titles(link_groups: &LinkGroups) -> Vec<&str> {
+        link_groups
+            .iter()
+            .flat_map(|group| &group.links)
+            .map(|link| link.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn previewing_title_replacements_changes_nothing() {
+        let mut link_groups = library();
+
+        let replacements = replace_low_quality_titles(&mut link_groups, true);
+
+        assert_eq!(replacements.len(), 1);
+        assert_eq!(replacements[0].old_title, "Loading...");
+        assert_eq!(replacements[0].new_title, "Fetched title");
+        assert_eq!(
+            titles(&link_groups),
+            ["Loading...", "Kept title", "New Tab"]
+        );
+    }
+
+    #[test]
+    fn applying_title_replacements_changes_only_poor_titles() {
+        let mut link_groups = library();
+        let first_id = link_groups[0].links[0].id;
+
+        let replacements = replace_low_quality_titles(&mut link_groups, false);
+
+        assert_eq!(replacements.len(), 1);
+        assert_eq!(replacements[0].link_id, first_id);
+        assert_eq!(
+            titles(&link_groups),
+            ["Fetched title", "Kept title", "New Tab"]
+        );
+    }
+}
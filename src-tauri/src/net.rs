@@ -0,0 +1,280 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::Read,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use ureq::{Agent, AgentBuilder, Response};
+use url::Url;
+
+const USER_AGENT: &str = concat!("BookmarkBay/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct FetchOptions {
+    pub concurrency: usize,
+    pub timeout_secs: u64,
+    // Minimum time between two requests to the same host
+    pub host_interval_ms: u64,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        FetchOptions {
+            concurrency: 8,
+            timeout_secs: 15,
+            host_interval_ms: 1000,
+        }
+    }
+}
+
+impl FetchOptions {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    pub fn host_interval(&self) -> Duration {
+        Duration::from_millis(self.host_interval_ms)
+    }
+}
+
+pub fn build_agent(timeout: Duration, redirects: u32) -> Agent {
+    AgentBuilder::new()
+        .timeout(timeout)
+        .redirects(redirects)
+        .user_agent(USER_AGENT)
+        .build()
+}
+
+// Error statuses are still responses as far as callers are concerned, only
+// transport failures are turned into errors
+pub fn send(request: ureq::Request) -> Result<Response, String> {
+    match request.call() {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => Ok(response),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Some servers don't implement HEAD, so fall back to GET when they say so
+pub fn head_or_get(agent: &Agent, uri: &str) -> Result<Response, String> {
+    let response = send(agent.head(uri))?;
+
+    if matches!(response.status(), 405 | 501) {
+        return send(agent.get(uri));
+    }
+
+    Ok(response)
+}
+
+// Plenty of servers answer HEAD with an error, or don't answer it at all, while
+// serving the page just fine. Where only getting somewhere matters, as when
+// following redirects, any failure is retried as a GET
+pub fn head_then_get(agent: &Agent, uri: &str) -> Result<Response, String> {
+    match send(agent.head(uri)) {
+        Ok(response) if response.status() < 400 => Ok(response),
+        _ => send(agent.get(uri)),
+    }
+}
+
+pub struct Fetched {
+    pub body: Vec<u8>,
+    pub content_type: String,
+}
+
+pub fn fetch(agent: &Agent, uri: &str, max_bytes: u64) -> Result<Fetched, String> {
+    let response = send(agent.get(uri))?;
+
+    if response.status() >= 400 {
+        return Err(format!("Server responded with {}", response.status()));
+    }
+
+    let content_type = response.content_type().to_string();
+    let mut body = Vec::new();
+
+    response
+        .into_reader()
+        .take(max_bytes)
+        .read_to_end(&mut body)
+        .map_err(|e| e.to_string())?;
+
+    Ok(Fetched { body, content_type })
+}
+
+pub fn fetch_html(agent: &Agent, uri: &str, max_bytes: u64) -> Result<String, String> {
+    let fetched = fetch(agent, uri, max_bytes)?;
+
+    if !fetched.content_type.contains("html") {
+        return Err(format!("Not an HTML page: {}", fetched.content_type));
+    }
+
+    Ok(String::from_utf8_lossy(&fetched.body).into_owned())
+}
+
+pub fn host_of(uri: &str) -> Option<String> {
+    Url::parse(uri)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_string()))
+}
+
+// Spaces out requests to the same host so that bulk jobs don't hammer a site
+// that many links happen to point at
+pub struct HostRateLimiter {
+    interval: Duration,
+    next_slots: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostRateLimiter {
+    pub fn new(interval: Duration) -> HostRateLimiter {
+        HostRateLimiter {
+            interval,
+            next_slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn wait(&self, uri: &str) {
+        let slot = {
+            let mut next_slots = self
+                .next_slots
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+            let host = host_of(uri).unwrap_or_default();
+            let now = Instant::now();
+
+            let slot = next_slots
+                .get(&host)
+                .copied()
+                .filter(|slot| *slot > now)
+                .unwrap_or(now);
+
+            next_slots.insert(host, slot + self.interval);
+
+            slot
+        };
+
+        thread::sleep(slot.saturating_duration_since(Instant::now()));
+    }
+}
+
+// Runs `job` over every item on at most `concurrency` threads, returning once all
+// items are done or `cancelled` is set
+pub fn run_pool<T, F>(items: Vec<T>, concurrency: usize, cancelled: &AtomicBool, job: F)
+where
+    T: Send,
+    F: Fn(T) + Sync,
+{
+    let workers = concurrency.clamp(1, items.len().max(1));
+    let queue = Mutex::new(VecDeque::from(items));
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                if cancelled.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                let item = queue
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .pop_front();
+
+                match item {
+                    Some(item) => job(item),
+                    None => break,
+                }
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[test]
+    fn rate_limiter_spaces_out_requests_to_the_same_host() {
+        let limiter = HostRateLimiter::new(Duration::from_millis(100));
+        let start = Instant::now();
+
+        limiter.wait("https://example.com/a");
+        limiter.wait("https://example.com/b");
+        limiter.wait("https://example.com/c");
+
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn rate_limiter_doesnt_wait_between_hosts() {
+        let limiter = HostRateLimiter::new(Duration::from_secs(10));
+        let start = Instant::now();
+
+        limiter.wait("https://example.com/");
+        limiter.wait("https://example.org/");
+        limiter.wait("https://www.example.com/");
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn pool_runs_every_item() {
+        let done = Mutex::new(Vec::new());
+
+        run_pool((0..50).collect(), 4, &AtomicBool::new(false), |item| {
+            done.lock().unwrap().push(item);
+        });
+
+        let mut done = done.into_inner().unwrap();
+        done.sort();
+
+        assert_eq!(done, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn pool_stays_within_its_concurrency() {
+        let running = AtomicUsize::new(0);
+        let most = AtomicUsize::new(0);
+
+        run_pool((0..20).collect(), 3, &AtomicBool::new(false), |_: usize| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            most.fetch_max(now, Ordering::SeqCst);
+
+            thread::sleep(Duration::from_millis(10));
+
+            running.fetch_sub(1, Ordering::SeqCst);
+        });
+
+        assert!(most.into_inner() <= 3);
+    }
+
+    #[test]
+    fn pool_handles_no_items() {
+        run_pool(Vec::<usize>::new(), 8, &AtomicBool::new(false), |_| {
+            panic!("There's nothing to run");
+        });
+    }
+
+    #[test]
+    fn pool_stops_once_cancelled() {
+        let cancelled = AtomicBool::new(false);
+        let done = AtomicUsize::new(0);
+
+        run_pool((0..100).collect(), 2, &cancelled, |item: usize| {
+            done.fetch_add(1, Ordering::SeqCst);
+
+            if item == 10 {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+        });
+
+        // Each worker finishes the item it's on at most
+        assert!(done.into_inner() <= 12);
+    }
+}
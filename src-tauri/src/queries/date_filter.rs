@@ -0,0 +1,69 @@
+use serde::Deserialize;
+
+use crate::types::{Link, LinkGroup};
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DateField {
+    Created,
+    Updated,
+    LastOpened,
+}
+
+impl DateField {
+    fn of_link(&self, link: &Link) -> Option<u64> {
+        match self {
+            DateField::Created => link.created_at,
+            DateField::Updated => link.updated_at,
+            DateField::LastOpened => link.last_opened_at,
+        }
+    }
+
+    fn of_group(&self, group: &LinkGroup) -> Option<u64> {
+        match self {
+            DateField::Created => group.created_at,
+            DateField::Updated => group.updated_at,
+            DateField::LastOpened => group.last_opened_at,
+        }
+    }
+}
+
+// Whether links are filtered by their own dates or by those of their group
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DateOf {
+    #[default]
+    Link,
+    Group,
+}
+
+// Times are in milliseconds since the unix epoch, `after` inclusive and `before`
+// exclusive. Links without the date never match
+#[derive(Deserialize, Debug)]
+pub struct DateFilter {
+    field: DateField,
+    #[serde(default)]
+    of: DateOf,
+    after: Option<u64>,
+    before: Option<u64>,
+}
+
+impl DateFilter {
+    fn matches_date(&self, date: Option<u64>) -> bool {
+        date.is_some_and(|date| {
+            self.after.is_none_or(|after| date >= after)
+                && self.before.is_none_or(|before| date < before)
+        })
+    }
+
+    pub fn matches(&self, group: &LinkGroup, link: &Link) -> bool {
+        match self.of {
+            DateOf::Link => self.matches_date(self.field.of_link(link)),
+            DateOf::Group => self.matches_group(group),
+        }
+    }
+
+    pub fn matches_group(&self, group: &LinkGroup) -> bool {
+        self.matches_date(self.field.of_group(group))
+    }
+}
@@ -1,72 +1,195 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
 
 use serde::Serialize;
-use tauri::State;
+use tauri::{AppHandle, State};
+
+use crate::{
+    folders::Folder,
+    rules::TagRule,
+    similarity, snapshots,
+    tag_tree::{self, TagIssues, TagNode},
+    types::{
+        CanonicalizeContainer, FoldersContainer, HistoryContainer, Link, LinkGroup, LinkGroups,
+        LinksContainer, StorableTag, TagRulesContainer, TagsContainer,
+    },
+};
+
+mod date_filter;
+pub mod sort;
+mod tag_filter;
+
+pub use date_filter::DateFilter;
+use sort::{GroupSortKey, SortKey};
+pub use tag_filter::TagFilter;
+
+// Groups can be filtered and sorted by their own dates, with pinned groups staying
+// above the rest whatever the order
+#[tauri::command]
+pub fn get_links(
+    links: State<LinksContainer>,
+    sort_by: Option<String>,
+    sort_direction: Option<String>,
+    date_filter: Option<DateFilter>,
+) -> Result<LinkGroups, String> {
+    let sort_key = GroupSortKey::try_from(sort_by.as_deref().unwrap_or("group_order"))?;
+    let descending = sort::is_descending(sort_direction.as_deref().unwrap_or("asc"))?;
+
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
 
-use crate::types::{Link, LinkGroup, LinkGroups, LinksContainer, TagsContainer};
+    let mut groups = link_groups
+        .iter()
+        .enumerate()
+        .filter(|(_, group)| {
+            date_filter
+                .as_ref()
+                .is_none_or(|date_filter| date_filter.matches_group(group))
+        })
+        .collect::<Vec<_>>();
+
+    groups.sort_by(|(a_pos, a), (b_pos, b)| {
+        let ordering = sort_key.compare(a, b).then_with(|| a_pos.cmp(b_pos));
+
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+
+    groups.sort_by_key(|(_, group)| !group.pinned);
+
+    Ok(groups
+        .into_iter()
+        .map(|(_, group)| group.to_owned())
+        .collect())
+}
+
+#[derive(Serialize)]
+pub struct SearchResult {
+    links: Vec<Link>,
+    tag_counts: BTreeMap<String, usize>,
+}
 
 #[tauri::command]
-pub fn get_links(links: State<LinksContainer>) -> Result<LinkGroups, String> {
+pub fn get_dead_links(links: State<LinksContainer>) -> Result<Vec<Link>, String> {
     let lock = links.lock().map_err(|e| e.to_string())?;
-    let links = lock.borrow();
+    let link_groups = lock.borrow();
 
-    Ok(links.to_owned())
+    Ok(link_groups
+        .iter()
+        .flat_map(|group| &group.links)
+        .filter(|link| link.health.as_ref().is_some_and(|health| health.is_dead()))
+        .cloned()
+        .collect())
 }
 
 #[tauri::command]
 pub fn search(
     links: State<LinksContainer>,
+    tags: State<TagsContainer>,
     search_text: Option<String>,
     sort_by: String,
     sort_direction: String,
-    tag: Option<String>,
-) -> Result<Vec<Link>, String> {
+    mut tag_filter: Option<TagFilter>,
+    date_filter: Option<DateFilter>,
+) -> Result<SearchResult, String> {
+    let sort_key = SortKey::try_from(sort_by.as_str())?;
+    let descending = sort::is_descending(&sort_direction)?;
+
     let lock = links.lock().map_err(|e| e.to_string())?;
     let link_groups = lock.borrow();
 
-    let mut base_iter = link_groups.iter().flat_map(|group| group.links.to_owned());
+    if let Some(ref mut tag_filter) = tag_filter {
+        let tags_lock = tags.lock().map_err(|e| e.to_string())?;
 
-    let mut filtered_iter;
+        tag_filter.resolve_aliases(&tags_lock.borrow());
+    }
 
-    let link_iter: &mut dyn Iterator<Item = Link>;
+    let search_text = search_text.map(|text| text.to_lowercase());
 
-    if let Some(ref text) = search_text {
-        filtered_iter = base_iter.filter(|link| {
-            !text.trim().is_empty() && link.title.to_lowercase().contains(&text.to_lowercase())
-        });
+    // Links are paired with their position across all groups so that group order
+    // can be used both as a sort key and as a tie breaker that ignores direction
+    let mut matched = link_groups
+        .iter()
+        .flat_map(|group| group.links.iter().map(move |link| (group, link)))
+        .enumerate()
+        .filter(|(_, (group, link))| match search_text {
+            Some(ref text) => {
+                !text.trim().is_empty()
+                    && (matches_text(link, text) || matches_group_text(group, text))
+            }
+            None => true,
+        })
+        .filter(|(_, (_, link))| {
+            tag_filter
+                .as_ref()
+                .is_none_or(|tag_filter| tag_filter.matches(link))
+        })
+        .filter(|(_, (group, link))| {
+            date_filter
+                .as_ref()
+                .is_none_or(|date_filter| date_filter.matches(group, link))
+        })
+        .collect::<Vec<_>>();
 
-        link_iter = &mut filtered_iter;
-    } else {
-        link_iter = &mut base_iter;
-    }
+    matched.sort_by(|(a_pos, (a_group, a)), (b_pos, (b_group, b))| {
+        let ordering = sort_key.compare((*a_pos, a_group, a), (*b_pos, b_group, b));
 
-    let mut links;
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+        .then_with(|| a_pos.cmp(b_pos))
+    });
 
-    if let Some(ref tag) = tag {
-        links = link_iter
-            .filter(|link| link.tags.contains(tag))
-            .collect::<Vec<Link>>();
-    } else {
-        links = link_iter.collect::<Vec<Link>>()
-    }
+    let links = matched
+        .into_iter()
+        .map(|(_, (_, link))| link.to_owned())
+        .collect::<Vec<Link>>();
 
-    links.sort_by_key(|link| match sort_by.as_str() {
-        "title" => link.title.to_owned(),
-        "uri" => link.uri.to_owned(),
-        _ => link.title.to_owned(),
-    });
+    let tag_counts = tag_filter::count_tags(&links);
+
+    Ok(SearchResult { links, tag_counts })
+}
+
+// Expects `text` to already be lowercased
+fn matches_text(link: &Link, text: &str) -> bool {
+    let contains = |field: &str| field.to_lowercase().contains(text);
 
-    if "desc" == sort_direction.as_str() {
-        links.reverse();
+    if contains(&link.title) || contains(&link.uri) || contains(link.domain()) {
+        return true;
     }
 
-    Ok(links)
+    if link.note.as_deref().is_some_and(contains) {
+        return true;
+    }
+
+    link.content.as_ref().is_some_and(|content| {
+        [&content.title, &content.description, &content.text]
+            .into_iter()
+            .flatten()
+            .any(|field| contains(field))
+    })
+}
+
+// Links match through their group's title and description too
+fn matches_group_text(group: &LinkGroup, text: &str) -> bool {
+    [&group.title, &group.description]
+        .into_iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(text))
 }
 
 #[derive(Default, Serialize)]
 pub struct ResolveDupeItem {
     dupes_left: usize,
     key: String,
+    link_ids: Vec<usize>,
     groups: Vec<LinkGroup>,
 }
 
@@ -77,41 +200,46 @@ impl PartialEq for ResolveDupeItem {
 }
 
 #[tauri::command]
-pub fn resolve_dupes(links: State<LinksContainer>) -> Result<ResolveDupeItem, String> {
+pub fn resolve_dupes(
+    links: State<LinksContainer>,
+    canonicalize: State<CanonicalizeContainer>,
+) -> Result<ResolveDupeItem, String> {
     let lock = links.lock().map_err(|e| e.to_string())?;
     let link_groups = lock.borrow();
 
-    let mut map = BTreeMap::<&String, Vec<&LinkGroup>>::new();
+    let canonicalize_lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = canonicalize_lock.borrow();
+
+    let mut map = BTreeMap::<String, (Vec<usize>, Vec<&LinkGroup>)>::new();
 
     for group in link_groups.iter() {
         for link in group.links.iter() {
-            if let Some(vec) = map.get_mut(&link.uri) {
-                if !vec.contains(&group) {
-                    vec.push(group);
-                }
-            } else {
-                let vec = vec![group];
+            let (link_ids, groups) = map
+                .entry(canonicalize_config.canonicalize(&link.uri))
+                .or_default();
+
+            link_ids.push(link.id);
 
-                map.insert(&link.uri, vec);
+            if !groups.contains(&group) {
+                groups.push(group);
             }
         }
     }
 
-    let mut iter = map.iter().filter(|(_, vec)| vec.len() > 1);
+    let mut iter = map.into_iter().filter(|(_, (_, groups))| groups.len() > 1);
 
     let result = iter
         .next()
-        .map(|(key, groups)| {
+        .map(|(key, (link_ids, groups))| {
             let groups = groups
                 .iter()
                 .map(|group| (*group).clone())
                 .collect::<Vec<LinkGroup>>();
 
-            let key = key.to_string();
-
             ResolveDupeItem {
                 dupes_left: 1 + iter.count(),
                 key,
+                link_ids,
                 groups,
             }
         })
@@ -120,12 +248,144 @@ pub fn resolve_dupes(links: State<LinksContainer>) -> Result<ResolveDupeItem, St
     Ok(result)
 }
 
+#[derive(Serialize)]
+pub struct NearDupeCluster {
+    similarity: f64,
+    link_ids: Vec<usize>,
+    groups: Vec<LinkGroup>,
+}
+
+#[tauri::command]
+pub fn find_near_dupes(
+    links: State<LinksContainer>,
+    canonicalize: State<CanonicalizeContainer>,
+    threshold: Option<f64>,
+) -> Result<Vec<NearDupeCluster>, String> {
+    let threshold = threshold.unwrap_or(0.7);
+
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(format!(
+            "Threshold must be between 0 and 1, got {}",
+            threshold
+        ));
+    }
+
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let canonicalize_lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = canonicalize_lock.borrow();
+
+    let flattened = link_groups
+        .iter()
+        .flat_map(|group| group.links.iter().map(move |link| (group, link)))
+        .collect::<Vec<_>>();
+
+    let mut clusters = similarity::find_clusters(
+        &flattened.iter().map(|(_, link)| *link).collect::<Vec<_>>(),
+        threshold,
+    )
+    .into_iter()
+    // Clusters of exact duplicates are already handled by `resolve_dupes`
+    .filter(|cluster| {
+        let keys = cluster
+            .members
+            .iter()
+            .map(|index| canonicalize_config.canonicalize(&flattened[*index].1.uri))
+            .collect::<BTreeSet<_>>();
+
+        keys.len() > 1
+    })
+    .map(|cluster| {
+        let mut groups = Vec::<LinkGroup>::new();
+
+        for index in cluster.members.iter() {
+            let (group, _) = flattened[*index];
+
+            if !groups.iter().any(|existing| existing.id == group.id) {
+                groups.push(group.clone());
+            }
+        }
+
+        NearDupeCluster {
+            similarity: cluster.similarity,
+            link_ids: cluster
+                .members
+                .iter()
+                .map(|index| flattened[*index].1.id)
+                .collect(),
+            groups,
+        }
+    })
+    .collect::<Vec<_>>();
+
+    clusters.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+
+    Ok(clusters)
+}
+
+#[tauri::command]
+pub fn get_tracking_params(
+    canonicalize: State<CanonicalizeContainer>,
+) -> Result<Vec<String>, String> {
+    let lock = canonicalize.lock().map_err(|e| e.to_string())?;
+    let canonicalize_config = lock.borrow();
+
+    Ok(canonicalize_config.tracking_params.to_owned())
+}
+
+#[tauri::command]
+pub fn get_folders(folders: State<FoldersContainer>) -> Result<Vec<Folder>, String> {
+    let lock = folders.lock().map_err(|e| e.to_string())?;
+    let folders = lock.borrow();
+
+    Ok(folders.to_owned())
+}
+
+// Labels of the operations that can be undone, most recent first
+#[tauri::command]
+pub fn get_history(history: State<HistoryContainer>) -> Result<Vec<String>, String> {
+    let lock = history.lock().map_err(|e| e.to_string())?;
+    let history = lock.borrow();
+
+    Ok(history.labels())
+}
+
+#[tauri::command]
+pub fn get_tag_rules(tag_rules: State<TagRulesContainer>) -> Result<Vec<TagRule>, String> {
+    let lock = tag_rules.lock().map_err(|e| e.to_string())?;
+    let tag_rules = lock.borrow();
+
+    Ok(tag_rules.to_owned())
+}
+
 #[tauri::command]
 pub fn get_tags(
+    links: State<LinksContainer>,
     tags: State<TagsContainer>,
     search_text: Option<String>,
+    sort_by: Option<String>,
     sort_direction: Option<String>,
 ) -> Result<Vec<String>, String> {
+    let descending = sort::is_descending(sort_direction.as_deref().unwrap_or("asc"))?;
+
+    // Links are locked before tags, matching the order used by the commands
+    let link_counts = match sort_by.as_deref().unwrap_or("name") {
+        "name" => None,
+        "link_count" => {
+            let lock = links.lock().map_err(|e| e.to_string())?;
+            let link_groups = lock.borrow();
+
+            let links = link_groups
+                .iter()
+                .flat_map(|group| group.links.iter().cloned())
+                .collect::<Vec<_>>();
+
+            Some(tag_filter::count_tags(&links))
+        }
+        key => return Err(format!("Unknown sort key: '{}'", key)),
+    };
+
     let lock = tags.lock().map_err(|e| e.to_string())?;
     let tags = lock.borrow();
 
@@ -133,19 +393,128 @@ pub fn get_tags(
 
     if let Some(ref search) = search_text {
         tags.iter()
-            .filter(|tag| tag.contains(search))
-            .for_each(|tag| result.push(tag.to_owned()))
+            .filter(|(tag, info)| {
+                tag.contains(search) || info.aliases.iter().any(|alias| alias.contains(search))
+            })
+            .for_each(|(tag, _)| result.push(tag.to_owned()))
     } else {
-        tags.iter().for_each(|tag| result.push(tag.to_owned()));
+        tags.keys().for_each(|tag| result.push(tag.to_owned()));
+    }
+
+    // The set is already ordered by name, and the sort is stable so ties keep that order
+    if let Some(counts) = link_counts {
+        result.sort_by_key(|tag| counts.get(tag).copied().unwrap_or_default());
     }
 
-    if Some("desc".to_string()) == sort_direction {
+    if descending {
         result.reverse();
     }
 
     Ok(result)
 }
 
+#[tauri::command]
+pub fn get_tag_tree(tags: State<TagsContainer>) -> Result<Vec<TagNode>, String> {
+    let lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = lock.borrow();
+
+    Ok(tag_tree::build_tree(tags.keys()))
+}
+
+#[tauri::command]
+pub fn get_tag_info(tags: State<TagsContainer>) -> Result<Vec<StorableTag>, String> {
+    let lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = lock.borrow();
+
+    Ok(tags
+        .iter()
+        .map(|(name, info)| StorableTag {
+            name: name.to_owned(),
+            info: info.to_owned(),
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+pub struct TagStats {
+    name: String,
+    link_count: usize,
+    group_count: usize,
+    // Most recent time a link with the tag was added or opened
+    last_used_at: Option<u64>,
+    // Whether the tag is in the tag list, rather than only used on links
+    listed: bool,
+}
+
+#[tauri::command]
+pub fn get_tag_stats(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+) -> Result<Vec<TagStats>, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    let mut stats = tags
+        .keys()
+        .map(|tag| {
+            (
+                tag.to_owned(),
+                TagStats {
+                    name: tag.to_owned(),
+                    link_count: 0,
+                    group_count: 0,
+                    last_used_at: None,
+                    listed: true,
+                },
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    for group in link_groups.iter() {
+        let mut seen_in_group = BTreeSet::<&String>::new();
+
+        for link in group.links.iter() {
+            let used_at = link.created_at.max(link.last_opened_at);
+
+            for tag in link.tags.iter() {
+                let entry = stats.entry(tag.to_owned()).or_insert_with(|| TagStats {
+                    name: tag.to_owned(),
+                    link_count: 0,
+                    group_count: 0,
+                    last_used_at: None,
+                    listed: false,
+                });
+
+                entry.link_count += 1;
+                entry.last_used_at = entry.last_used_at.max(used_at);
+
+                if seen_in_group.insert(tag) {
+                    entry.group_count += 1;
+                }
+            }
+        }
+    }
+
+    Ok(stats.into_values().collect())
+}
+
+#[tauri::command]
+pub fn find_tag_issues(
+    links: State<LinksContainer>,
+    tags: State<TagsContainer>,
+) -> Result<TagIssues, String> {
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let tags_lock = tags.lock().map_err(|e| e.to_string())?;
+    let tags = tags_lock.borrow();
+
+    Ok(TagIssues::find(&link_groups, &tags))
+}
+
 #[tauri::command]
 pub fn get_tags_for_link(
     links: State<LinksContainer>,
@@ -161,6 +530,46 @@ pub fn get_tags_for_link(
 
     match link {
         Some(link) => Ok(link.tags.to_owned()),
-        None => Ok(Vec::new()),
+        None => Err(format!("Link {} wasn't found", id)),
     }
 }
+
+#[derive(Serialize)]
+pub struct SnapshotUsage {
+    archived_links: usize,
+    files: usize,
+    total_bytes: u64,
+    orphaned_files: usize,
+    orphaned_bytes: u64,
+}
+
+#[tauri::command]
+pub fn get_snapshot_usage(
+    app: AppHandle,
+    links: State<LinksContainer>,
+) -> Result<SnapshotUsage, String> {
+    let files = snapshots::stored_files(&snapshots::snapshots_dir(&app)?)?;
+
+    let lock = links.lock().map_err(|e| e.to_string())?;
+    let link_groups = lock.borrow();
+
+    let referenced = link_groups
+        .iter()
+        .flat_map(|group| &group.links)
+        .filter_map(|link| link.snapshot.as_ref())
+        .map(|snapshot| PathBuf::from(&snapshot.path))
+        .collect::<Vec<_>>();
+
+    let orphaned = files
+        .iter()
+        .filter(|(path, _)| !referenced.contains(path))
+        .collect::<Vec<_>>();
+
+    Ok(SnapshotUsage {
+        archived_links: referenced.len(),
+        files: files.len(),
+        total_bytes: files.iter().map(|(_, size)| size).sum(),
+        orphaned_files: orphaned.len(),
+        orphaned_bytes: orphaned.iter().map(|(_, size)| size).sum(),
+    })
+}
@@ -0,0 +1,110 @@
+use std::cmp::Ordering;
+
+use crate::types::{Link, LinkGroup};
+
+pub enum SortKey {
+    Title,
+    Uri,
+    Domain,
+    DateAdded,
+    LastUpdated,
+    GroupOrder,
+    TagCount,
+    LastOpened,
+    // Groups' dates keep the links of each group together, in group order
+    GroupCreated,
+    GroupUpdated,
+    GroupLastOpened,
+}
+
+impl TryFrom<&str> for SortKey {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "title" => Ok(SortKey::Title),
+            "uri" => Ok(SortKey::Uri),
+            "domain" => Ok(SortKey::Domain),
+            "date_added" => Ok(SortKey::DateAdded),
+            "last_updated" => Ok(SortKey::LastUpdated),
+            "group_order" => Ok(SortKey::GroupOrder),
+            "tag_count" => Ok(SortKey::TagCount),
+            "last_opened" => Ok(SortKey::LastOpened),
+            "group_created" => Ok(SortKey::GroupCreated),
+            "group_updated" => Ok(SortKey::GroupUpdated),
+            "group_last_opened" => Ok(SortKey::GroupLastOpened),
+            _ => Err(format!("Unknown sort key: '{}'", value)),
+        }
+    }
+}
+
+pub fn is_descending(sort_direction: &str) -> Result<bool, String> {
+    match sort_direction {
+        "asc" => Ok(false),
+        "desc" => Ok(true),
+        _ => Err(format!("Unknown sort direction: '{}'", sort_direction)),
+    }
+}
+
+impl SortKey {
+    // Links are passed along with their position across all groups and the group
+    // they're in. Ties on the primary key fall back to the title, except for group
+    // dates, and remaining ties are left to the caller
+    pub fn compare(
+        &self,
+        (a_pos, a_group, a): (usize, &LinkGroup, &Link),
+        (b_pos, b_group, b): (usize, &LinkGroup, &Link),
+    ) -> Ordering {
+        let by_title = || a.title.cmp(&b.title);
+
+        match self {
+            SortKey::Title => by_title().then_with(|| a.uri.cmp(&b.uri)),
+            SortKey::Uri => a.uri.cmp(&b.uri).then_with(by_title),
+            SortKey::Domain => a.domain().cmp(b.domain()).then_with(by_title),
+            SortKey::DateAdded => a.created_at.cmp(&b.created_at).then_with(by_title),
+            SortKey::LastUpdated => a.updated_at.cmp(&b.updated_at).then_with(by_title),
+            SortKey::GroupOrder => a_pos.cmp(&b_pos),
+            SortKey::TagCount => a.tags.len().cmp(&b.tags.len()).then_with(by_title),
+            SortKey::LastOpened => a.last_opened_at.cmp(&b.last_opened_at).then_with(by_title),
+            SortKey::GroupCreated => a_group.created_at.cmp(&b_group.created_at),
+            SortKey::GroupUpdated => a_group.updated_at.cmp(&b_group.updated_at),
+            SortKey::GroupLastOpened => a_group.last_opened_at.cmp(&b_group.last_opened_at),
+        }
+    }
+}
+
+pub enum GroupSortKey {
+    GroupOrder,
+    Title,
+    DateAdded,
+    LastUpdated,
+    LastOpened,
+}
+
+impl TryFrom<&str> for GroupSortKey {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "group_order" => Ok(GroupSortKey::GroupOrder),
+            "title" => Ok(GroupSortKey::Title),
+            "date_added" => Ok(GroupSortKey::DateAdded),
+            "last_updated" => Ok(GroupSortKey::LastUpdated),
+            "last_opened" => Ok(GroupSortKey::LastOpened),
+            _ => Err(format!("Unknown sort key: '{}'", value)),
+        }
+    }
+}
+
+impl GroupSortKey {
+    // Ties are left to the caller
+    pub fn compare(&self, a: &LinkGroup, b: &LinkGroup) -> Ordering {
+        match self {
+            GroupSortKey::GroupOrder => Ordering::Equal,
+            GroupSortKey::Title => a.title.cmp(&b.title),
+            GroupSortKey::DateAdded => a.created_at.cmp(&b.created_at),
+            GroupSortKey::LastUpdated => a.updated_at.cmp(&b.updated_at),
+            GroupSortKey::LastOpened => a.last_opened_at.cmp(&b.last_opened_at),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::{
+    tag_tree,
+    types::{Link, Tags},
+};
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct TagFilter {
+    all: Vec<String>,
+    any: Vec<String>,
+    none: Vec<String>,
+    // Overrides the other fields when set
+    untagged: bool,
+}
+
+impl TagFilter {
+    pub fn resolve_aliases(&mut self, tags: &Tags) {
+        for tag in self
+            .all
+            .iter_mut()
+            .chain(&mut self.any)
+            .chain(&mut self.none)
+        {
+            *tag = tag_tree::resolve(tags, tag);
+        }
+    }
+
+    pub fn matches(&self, link: &Link) -> bool {
+        if self.untagged {
+            return link.tags.is_empty();
+        }
+
+        // Parent tags match any of the tags underneath them
+        let has_tag = |tag: &String| {
+            link.tags
+                .iter()
+                .any(|link_tag| tag_tree::is_within(link_tag, tag))
+        };
+
+        self.all.iter().all(has_tag)
+            && (self.any.is_empty() || self.any.iter().any(has_tag))
+            && !self.none.iter().any(has_tag)
+    }
+}
+
+pub fn count_tags(links: &[Link]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::<String, usize>::new();
+
+    for tag in links.iter().flat_map(|link| &link.tags) {
+        *counts.entry(tag.to_owned()).or_default() += 1;
+    }
+
+    counts
+}
@@ -0,0 +1,269 @@
+use serde::Serialize;
+use tauri::AppHandle;
+use ureq::Agent;
+use url::Url;
+
+use crate::{jobs, net};
+
+pub const JOB_NAME: &str = "redirect_expansion";
+
+pub const MAX_HOPS: usize = 10;
+
+// Hosts whose links only ever exist to redirect somewhere else
+const SHORTENER_HOSTS: [&str; 20] = [
+    "t.co",
+    "bit.ly",
+    "bitly.com",
+    "tinyurl.com",
+    "goo.gl",
+    "ow.ly",
+    "buff.ly",
+    "is.gd",
+    "lnkd.in",
+    "dlvr.it",
+    "fb.me",
+    "amzn.to",
+    "rebrand.ly",
+    "shorturl.at",
+    "tiny.cc",
+    "cutt.ly",
+    "t.ly",
+    "trib.al",
+    "feeds.feedburner.com",
+    "feedproxy.google.com",
+];
+
+#[derive(Serialize, Clone)]
+pub struct Expansion {
+    link_id: usize,
+    original_uri: String,
+    final_uri: Option<String>,
+    hops: usize,
+    error: Option<String>,
+    applied: bool,
+}
+
+pub fn is_short_link(uri: &str) -> bool {
+    net::host_of(uri).is_some_and(|host| {
+        let host = host.to_ascii_lowercase();
+
+        SHORTENER_HOSTS.contains(&host.strip_prefix("www.").unwrap_or(&host))
+    })
+}
+
+// Follows redirects one hop at a time so that the number of hops can be capped
+// and reported. Expects an agent that doesn't follow redirects itself
+pub fn resolve(agent: &Agent, uri: &str) -> Result<(String, usize), String> {
+    let mut current = Url::parse(uri).map_err(|e| e.to_string())?;
+
+    for hop in 0..=MAX_HOPS {
+        let response = net::head_then_get(agent, current.as_str())?;
+
+        if !matches!(response.status(), 301 | 302 | 303 | 307 | 308) {
+            return Ok((current.to_string(), hop));
+        }
+
+        let location = response
+            .header("location")
+            .ok_or(format!("Redirect without a location from '{}'", current))?;
+
+        current = current.join(location).map_err(|e| e.to_string())?;
+    }
+
+    Err(format!("Gave up after {} redirects", MAX_HOPS))
+}
+
+// When applying, the link is only rewritten if it was actually redirected and its
+// URI hasn't been changed while it was being resolved. The URI it had is kept as
+// an alias
+pub fn record(
+    app: &AppHandle,
+    link_id: usize,
+    original_uri: String,
+    resolved: Result<(String, usize), String>,
+    apply: bool,
+) -> Expansion {
+    let (final_uri, hops) = match resolved {
+        Ok(resolved) => resolved,
+        Err(error) => {
+            return Expansion {
+                link_id,
+                original_uri,
+                final_uri: None,
+                hops: 0,
+                error: Some(error),
+                applied: false,
+            };
+        }
+    };
+
+    // Without hops the final URI only differs from the original by how it's written
+    let applied = apply
+        && hops > 0
+        && final_uri != original_uri
+        && jobs::update_link(app, link_id, |link| {
+            if link.uri != original_uri {
+                return false;
+            }
+
+            // Aliases still lead to the same page, unlike the link's other details
+            let mut aliases = std::mem::take(&mut link.aliases);
+            let previous = link.uri.to_owned();
+
+            link.set_uri(final_uri.to_owned());
+
+            aliases.retain(|alias| alias != &final_uri);
+
+            if !aliases.contains(&previous) {
+                aliases.push(previous);
+            }
+
+            link.aliases = aliases;
+            link.touch();
+
+            true
+        })
+        .unwrap_or_default();
+
+    Expansion {
+        link_id,
+        original_uri,
+        final_uri: Some(final_uri),
+        hops,
+        error: None,
+        applied,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::test_server::{Response, TestServer};
+
+    fn agent() -> Agent {
+        net::build_agent(Duration::from_secs(5), 0)
+    }
+
+    // Serves `/hop/n` as a redirect to `/hop/n+1` until `hops`, where the page is
+    fn chain(hops: usize) -> TestServer {
+        TestServer::start(move |request| {
+            let hop = request
+                .path
+                .strip_prefix("/hop/")
+                .and_then(|hop| hop.parse::<usize>().ok());
+
+            match hop {
+                Some(hop) if hop < hops => Response::redirect(302, &format!("/hop/{}", hop + 1)),
+                Some(_) => Response::html("<title>Done</title>"),
+                None => Response::new(404),
+            }
+        })
+    }
+
+    #[test]
+    fn follows_a_chain_of_redirects() {
+        let server = chain(3);
+
+        let resolved = resolve(&agent(), &server.url("/hop/0"));
+
+        assert_eq!(resolved, Ok((server.url("/hop/3"), 3)));
+    }
+
+    #[test]
+    fn stops_right_away_without_a_redirect() {
+        let server = chain(0);
+
+        let resolved = resolve(&agent(), &server.url("/hop/0"));
+
+        assert_eq!(resolved, Ok((server.url("/hop/0"), 0)));
+    }
+
+    #[test]
+    fn follows_up_to_max_hops() {
+        let server = chain(MAX_HOPS);
+
+        let resolved = resolve(&agent(), &server.url("/hop/0"));
+
+        assert_eq!(
+            resolved,
+            Ok((server.url(&format!("/hop/{}", MAX_HOPS)), MAX_HOPS))
+        );
+    }
+
+    #[test]
+    fn gives_up_past_max_hops() {
+        let server = chain(MAX_HOPS + 1);
+
+        let resolved = resolve(&agent(), &server.url("/hop/0"));
+
+        assert_eq!(
+            resolved,
+            Err(format!("Gave up after {} redirects", MAX_HOPS))
+        );
+    }
+
+    #[test]
+    fn gives_up_on_a_loop() {
+        let server = TestServer::start(|request| match request.path.as_str() {
+            "/a" => Response::redirect(301, "/b"),
+            _ => Response::redirect(301, "/a"),
+        });
+
+        let resolved = resolve(&agent(), &server.url("/a"));
+
+        assert_eq!(
+            resolved,
+            Err(format!("Gave up after {} redirects", MAX_HOPS))
+        );
+    }
+
+    #[test]
+    fn resolves_relative_locations() {
+        let server = TestServer::start(|request| match request.path.as_str() {
+            "/short/abc" => Response::redirect(301, "../posts/1?ref=short"),
+            "/posts/1?ref=short" => Response::redirect(307, "2"),
+            "/posts/2" => Response::redirect(308, "/final"),
+            _ => Response::html("<title>Post</title>"),
+        });
+
+        let resolved = resolve(&agent(), &server.url("/short/abc"));
+
+        assert_eq!(resolved, Ok((server.url("/final"), 3)));
+    }
+
+    #[test]
+    fn fails_on_a_redirect_without_a_location() {
+        let server = TestServer::start(|_| Response::new(302));
+
+        let resolved = resolve(&agent(), &server.url("/nowhere"));
+
+        assert!(resolved.is_err_and(|error| error.starts_with("Redirect without a location")));
+    }
+
+    #[test]
+    fn retries_failed_head_requests_as_get() {
+        let server =
+            TestServer::start(
+                |request| match (request.method.as_str(), request.path.as_str()) {
+                    ("HEAD", _) => Response::new(403),
+                    (_, "/short") => Response::redirect(302, "/page"),
+                    _ => Response::html("<title>Page</title>"),
+                },
+            );
+
+        let resolved = resolve(&agent(), &server.url("/short"));
+
+        // The redirect only shows up to GET
+        assert_eq!(resolved, Ok((server.url("/page"), 1)));
+
+        let methods = server
+            .requests()
+            .into_iter()
+            .map(|request| request.method)
+            .collect::<Vec<_>>();
+
+        assert_eq!(methods, ["HEAD", "GET", "HEAD", "GET"]);
+    }
+}
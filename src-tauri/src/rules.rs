@@ -0,0 +1,399 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    tag_tree,
+    types::{get_new_id, Link, LinkGroups, Tags},
+};
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum RuleMatcher {
+    // Matches the domain itself along with any of its subdomains
+    Domain(String),
+    // `*` matches any run of characters and `?` a single one, against the whole URI
+    Glob(String),
+    Regex(String),
+    TitleKeyword(String),
+    // Groups are matched by position, as their ids aren't kept between saves
+    Group(usize),
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct TagRule {
+    pub id: usize,
+    pub matcher: RuleMatcher,
+    pub tags: Vec<String>,
+    pub enabled: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct StorableTagRule {
+    pub matcher: RuleMatcher,
+    pub tags: Vec<String>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl From<StorableTagRule> for TagRule {
+    fn from(value: StorableTagRule) -> Self {
+        TagRule {
+            id: get_new_id(),
+            matcher: value.matcher,
+            tags: value.tags,
+            enabled: value.enabled,
+        }
+    }
+}
+
+impl From<TagRule> for StorableTagRule {
+    fn from(value: TagRule) -> Self {
+        StorableTagRule {
+            matcher: value.matcher,
+            tags: value.tags,
+            enabled: value.enabled,
+        }
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut pattern = String::from("(?i)^");
+
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    pattern.push('$');
+
+    pattern
+}
+
+// A rule with its pattern compiled, so it can be checked against many links
+pub struct CompiledRule<'a> {
+    pub rule: &'a TagRule,
+    pattern: Option<Regex>,
+}
+
+impl CompiledRule<'_> {
+    pub fn matches(&self, link: &Link, group_position: usize) -> bool {
+        match self.rule.matcher {
+            RuleMatcher::Domain(ref domain) => {
+                let host = link.domain().to_lowercase();
+                let host = host.strip_prefix("www.").unwrap_or(&host);
+                let domain = domain.to_lowercase();
+
+                host == domain
+                    || host
+                        .strip_suffix(&domain)
+                        .is_some_and(|rest| rest.ends_with('.'))
+            }
+            RuleMatcher::Glob(_) | RuleMatcher::Regex(_) => self
+                .pattern
+                .as_ref()
+                .is_some_and(|pattern| pattern.is_match(&link.uri)),
+            RuleMatcher::TitleKeyword(ref keyword) => {
+                link.title.to_lowercase().contains(&keyword.to_lowercase())
+            }
+            RuleMatcher::Group(position) => position == group_position,
+        }
+    }
+
+    // The rule's tags the link doesn't have yet
+    pub fn missing_tags(&self, link: &Link) -> Vec<String> {
+        self.rule
+            .tags
+            .iter()
+            .filter(|tag| !link.tags.contains(tag))
+            .cloned()
+            .collect()
+    }
+}
+
+pub fn compile(rule: &TagRule) -> Result<CompiledRule<'_>, String> {
+    let pattern = match rule.matcher {
+        RuleMatcher::Glob(ref glob) => Some(glob_to_regex(glob.trim())),
+        RuleMatcher::Regex(ref regex) => Some(regex.to_owned()),
+        _ => None,
+    };
+
+    let pattern = pattern
+        .map(|pattern| Regex::new(&pattern))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+
+    Ok(CompiledRule { rule, pattern })
+}
+
+pub fn compile_enabled(rules: &[TagRule]) -> Result<Vec<CompiledRule<'_>>, String> {
+    rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .map(compile)
+        .collect()
+}
+
+// Checks the pattern compiles and the tags are valid, resolving any aliases
+pub fn validate(
+    matcher: &RuleMatcher,
+    tags: &[String],
+    known_tags: &Tags,
+) -> Result<Vec<String>, String> {
+    match matcher {
+        RuleMatcher::Domain(value)
+        | RuleMatcher::TitleKeyword(value)
+        | RuleMatcher::Glob(value)
+            if value.trim().is_empty() =>
+        {
+            return Err("Rule needs something to match on".to_string());
+        }
+        RuleMatcher::Regex(regex) => {
+            Regex::new(regex).map_err(|e| e.to_string())?;
+        }
+        _ => {}
+    }
+
+    if tags.is_empty() {
+        return Err("Rule needs at least one tag".to_string());
+    }
+
+    let mut resolved = Vec::<String>::with_capacity(tags.len());
+
+    for tag in tags {
+        let tag = tag_tree::resolve(known_tags, &tag_tree::normalize(tag)?);
+
+        if !resolved.contains(&tag) {
+            resolved.push(tag);
+        }
+    }
+
+    Ok(resolved)
+}
+
+// Applies the rules to the links `filter` accepts, adding any tags they use to the
+// tag list. Returns the ids of the links each rule tagged, in the order of `rules`,
+// which with `dry_run` are the links it would tag without anything being changed
+pub fn apply<F>(
+    rules: &[CompiledRule],
+    link_groups: &mut LinkGroups,
+    tags: &mut Tags,
+    dry_run: bool,
+    filter: F,
+) -> Vec<Vec<usize>>
+where
+    F: Fn(&Link) -> bool,
+{
+    let mut tagged = vec![Vec::<usize>::new(); rules.len()];
+
+    for (group_position, group) in link_groups.iter_mut().enumerate() {
+        for link in group.links.iter_mut().filter(|link| filter(link)) {
+            for (rule, tagged) in rules.iter().zip(tagged.iter_mut()) {
+                if !rule.matches(link, group_position) {
+                    continue;
+                }
+
+                let missing = rule.missing_tags(link);
+
+                if missing.is_empty() {
+                    continue;
+                }
+
+                tagged.push(link.id);
+
+                if dry_run {
+                    continue;
+                }
+
+                for tag in missing {
+                    tags.entry(tag.to_owned()).or_default();
+                    link.tags.push(tag);
+                }
+            }
+        }
+    }
+
+    tagged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{LinkGroup, TagInfo};
+
+    fn rule(matcher: RuleMatcher, tags: &[&str]) -> TagRule {
+        TagRule {
+            id: get_new_id(),
+            matcher,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            enabled: true,
+        }
+    }
+
+    fn link(uri: &str, title: &str) -> Link {
+        Link::new(uri.to_string(), title.to_string(), Vec::new())
+    }
+
+    fn matches(matcher: RuleMatcher, link: &Link) -> bool {
+        compile(&rule(matcher, &["tag"])).unwrap().matches(link, 0)
+    }
+
+    #[test]
+    fn domains_match_themselves_and_subdomains() {
+        let domain = || RuleMatcher::Domain("Example.com".to_string());
+
+        assert!(matches(domain(), &link("https://example.com/page", "")));
+        assert!(matches(domain(), &link("https://www.example.com/", "")));
+        assert!(matches(domain(), &link("https://docs.example.com/", "")));
+        assert!(!matches(domain(), &link("https://notexample.com/", "")));
+        assert!(!matches(
+            domain(),
+            &link("https://example.com.evil.org/", "")
+        ));
+    }
+
+    #[test]
+    fn globs_match_the_whole_uri_ignoring_case() {
+        let glob = || RuleMatcher::Glob("https://github.com/*/issues/?".to_string());
+
+        assert!(matches(
+            glob(),
+            &link("https://GitHub.com/rust-lang/issues/1", "")
+        ));
+        assert!(!matches(
+            glob(),
+            &link("https://github.com/rust-lang/issues/12", "")
+        ));
+        assert!(!matches(
+            glob(),
+            &link("http://example.com/https://github.com/a/issues/1", "")
+        ));
+    }
+
+    #[test]
+    fn globs_escape_everything_else() {
+        let glob = RuleMatcher::Glob("https://example.com/a+b.html".to_string());
+
+        assert!(matches(
+            glob.clone(),
+            &link("https://example.com/a+b.html", "")
+        ));
+        assert!(!matches(glob, &link("https://example.com/aab.html", "")));
+    }
+
+    #[test]
+    fn regexes_titles_and_groups_match() {
+        let uri = "https://example.com/posts/42";
+
+        assert!(matches(
+            RuleMatcher::Regex(r"/posts/\d+$".to_string()),
+            &link(uri, "")
+        ));
+        assert!(matches(
+            RuleMatcher::TitleKeyword("rust".to_string()),
+            &link(uri, "Learning Rust")
+        ));
+        assert!(!matches(
+            RuleMatcher::TitleKeyword("rust".to_string()),
+            &link(uri, "Learning Go")
+        ));
+
+        let group_rule = rule(RuleMatcher::Group(2), &["tag"]);
+        let group = compile(&group_rule).unwrap();
+
+        assert!(group.matches(&link(uri, ""), 2));
+        assert!(!group.matches(&link(uri, ""), 1));
+    }
+
+    #[test]
+    fn only_enabled_rules_are_compiled() {
+        let mut broken = rule(RuleMatcher::Regex("(".to_string()), &["tag"]);
+
+        assert!(compile_enabled(std::slice::from_ref(&broken)).is_err());
+
+        broken.enabled = false;
+
+        assert_eq!(compile_enabled(&[broken]).map(|rules| rules.len()), Ok(0));
+    }
+
+    #[test]
+    fn validation_normalizes_tags_and_resolves_aliases() {
+        let mut tags = Tags::new();
+
+        tags.insert(
+            "javascript".to_string(),
+            TagInfo {
+                aliases: vec!["js".to_string()],
+                ..TagInfo::default()
+            },
+        );
+
+        let matcher = RuleMatcher::Domain("example.com".to_string());
+        let rule_tags = [
+            "js".to_string(),
+            " dev / web ".to_string(),
+            "javascript".to_string(),
+        ];
+
+        assert_eq!(
+            validate(&matcher, &rule_tags, &tags),
+            Ok(vec!["javascript".to_string(), "dev/web".to_string()])
+        );
+    }
+
+    #[test]
+    fn validation_rejects_incomplete_rules() {
+        let tags = Tags::new();
+        let tag = ["tag".to_string()];
+
+        assert!(validate(&RuleMatcher::Domain(" ".to_string()), &tag, &tags).is_err());
+        assert!(validate(&RuleMatcher::Regex("[".to_string()), &tag, &tags).is_err());
+        assert!(validate(&RuleMatcher::Group(0), &[], &tags).is_err());
+        assert!(validate(&RuleMatcher::Group(0), &["a//b".to_string()], &tags).is_err());
+    }
+
+    #[test]
+    fn applying_adds_missing_tags_unless_dry_run() {
+        let rules = [
+            rule(RuleMatcher::Domain("example.com".to_string()), &["example"]),
+            rule(RuleMatcher::TitleKeyword("rust".to_string()), &["rust"]),
+        ];
+        let compiled = compile_enabled(&rules).unwrap();
+
+        let mut tagged_already = link("https://example.com/rust", "Rust");
+        tagged_already.tags = vec!["example".to_string()];
+
+        let other = link("https://other.org/", "Other");
+        let ids = [tagged_already.id, other.id];
+
+        let mut link_groups = LinkGroups::from([LinkGroup::new([tagged_already, other].into())]);
+        let mut tags = Tags::new();
+
+        let before = link_groups.clone();
+        let tagged = apply(&compiled, &mut link_groups, &mut tags, true, |_| true);
+
+        assert_eq!(tagged, vec![Vec::new(), vec![ids[0]]]);
+        assert!(link_groups == before && tags.is_empty());
+
+        let tagged = apply(&compiled, &mut link_groups, &mut tags, false, |_| true);
+
+        assert_eq!(tagged, vec![Vec::new(), vec![ids[0]]]);
+        assert_eq!(link_groups[0].links[0].tags, vec!["example", "rust"]);
+        assert!(link_groups[0].links[1].tags.is_empty());
+        assert!(tags.contains_key("rust"));
+
+        // Links the filter skips are left alone
+        let mut link_groups = before;
+        let tagged = apply(&compiled, &mut link_groups, &mut tags, false, |link| {
+            link.id != ids[0]
+        });
+
+        assert_eq!(tagged, vec![Vec::<usize>::new(), Vec::new()]);
+    }
+}
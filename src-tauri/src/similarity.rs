@@ -0,0 +1,232 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet},
+    hash::{Hash, Hasher},
+};
+
+use url::Url;
+
+use crate::types::Link;
+
+const SHINGLE_SIZE: usize = 3;
+
+// 32 MinHash values split into 16 bands of 2 rows, which makes pairs with a title
+// similarity of around 0.25 and up likely to end up as candidates
+const BANDS: usize = 16;
+const ROWS_PER_BAND: usize = 2;
+
+const TITLE_WEIGHT: f64 = 0.75;
+
+// Prefixes that usually point at another rendering of the same page
+const HOST_PREFIXES: [&str; 4] = ["www.", "m.", "mobile.", "amp."];
+
+// Path segments that don't tell pages apart
+const IGNORED_PATH_TOKENS: [&str; 6] = ["amp", "index", "html", "htm", "php", "www"];
+
+struct Fingerprint {
+    shingles: BTreeSet<String>,
+    host: String,
+    path_tokens: BTreeSet<String>,
+}
+
+pub struct Cluster {
+    // Indexes into the links passed to `find_clusters`, in their original order
+    pub members: Vec<usize>,
+    // The lowest score among the pairs that joined the cluster together
+    pub similarity: f64,
+}
+
+fn normalize_title(title: &str) -> String {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn fingerprint(link: &Link) -> Fingerprint {
+    let title = normalize_title(&link.title).chars().collect::<Vec<_>>();
+
+    let shingles = title
+        .windows(SHINGLE_SIZE)
+        .map(|window| window.iter().collect::<String>())
+        .collect();
+
+    let (host, path) = match Url::parse(&link.uri) {
+        Ok(url) => (
+            url.host_str().unwrap_or_default().to_string(),
+            url.path().to_lowercase(),
+        ),
+        Err(_) => (String::new(), link.uri.to_lowercase()),
+    };
+
+    let host = HOST_PREFIXES
+        .iter()
+        .fold(host.as_str(), |host, prefix| {
+            host.strip_prefix(prefix).unwrap_or(host)
+        })
+        .to_string();
+
+    let path_tokens = path
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty() && !IGNORED_PATH_TOKENS.contains(token))
+        .map(|token| token.to_string())
+        .collect();
+
+    Fingerprint {
+        shingles,
+        host,
+        path_tokens,
+    }
+}
+
+fn hash_with_seed<T: Hash>(seed: usize, value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    seed.hash(&mut hasher);
+    value.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+fn min_hashes(shingles: &BTreeSet<String>) -> [u64; BANDS * ROWS_PER_BAND] {
+    let mut hashes = [u64::MAX; BANDS * ROWS_PER_BAND];
+
+    for (seed, hash) in hashes.iter_mut().enumerate() {
+        for shingle in shingles {
+            *hash = (*hash).min(hash_with_seed(seed, shingle));
+        }
+    }
+
+    hashes
+}
+
+fn jaccard(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+
+    if union == 0 {
+        return 0.0;
+    }
+
+    a.intersection(b).count() as f64 / union as f64
+}
+
+fn score(a: &Fingerprint, b: &Fingerprint) -> f64 {
+    let title = jaccard(&a.shingles, &b.shingles);
+
+    let same_host = if !a.host.is_empty() && a.host == b.host {
+        1.0
+    } else {
+        0.0
+    };
+
+    let structure = if a.path_tokens.is_empty() && b.path_tokens.is_empty() {
+        same_host
+    } else {
+        (same_host + jaccard(&a.path_tokens, &b.path_tokens)) / 2.0
+    };
+
+    TITLE_WEIGHT * title + (1.0 - TITLE_WEIGHT) * structure
+}
+
+fn find_root(parents: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+
+    while parents[root] != root {
+        root = parents[root];
+    }
+
+    // Point everything on the way straight at the root to keep later lookups short
+    let mut current = index;
+
+    while parents[current] != root {
+        let next = parents[current];
+
+        parents[current] = root;
+        current = next;
+    }
+
+    root
+}
+
+pub fn find_clusters(links: &[&Link], threshold: f64) -> Vec<Cluster> {
+    let fingerprints = links
+        .iter()
+        .map(|link| fingerprint(link))
+        .collect::<Vec<_>>();
+
+    let mut buckets = BTreeMap::<(usize, u64), Vec<usize>>::new();
+
+    for (index, fingerprint) in fingerprints.iter().enumerate() {
+        // Titles this short match far too much to be useful
+        if fingerprint.shingles.len() < SHINGLE_SIZE {
+            continue;
+        }
+
+        let hashes = min_hashes(&fingerprint.shingles);
+
+        for (band, rows) in hashes.chunks(ROWS_PER_BAND).enumerate() {
+            buckets
+                .entry((band, hash_with_seed(band, &rows)))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let candidates = buckets
+        .values()
+        .flat_map(|bucket| {
+            bucket
+                .iter()
+                .enumerate()
+                .flat_map(move |(i, a)| bucket[i + 1..].iter().map(move |b| (*a, *b)))
+        })
+        .collect::<BTreeSet<_>>();
+
+    let mut parents = (0..links.len()).collect::<Vec<_>>();
+    let mut similarities = BTreeMap::<usize, f64>::new();
+
+    for (a, b) in candidates {
+        let score = score(&fingerprints[a], &fingerprints[b]);
+
+        if score < threshold {
+            continue;
+        }
+
+        let root_a = find_root(&mut parents, a);
+        let root_b = find_root(&mut parents, b);
+
+        if root_a == root_b {
+            continue;
+        }
+
+        let similarity = [
+            Some(score),
+            similarities.remove(&root_a),
+            similarities.remove(&root_b),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(f64::MAX, f64::min);
+
+        parents[root_b] = root_a;
+        similarities.insert(root_a, similarity);
+    }
+
+    let mut clusters = BTreeMap::<usize, Vec<usize>>::new();
+
+    for index in 0..links.len() {
+        let root = find_root(&mut parents, index);
+
+        clusters.entry(root).or_default().push(index);
+    }
+
+    clusters
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(root, members)| Cluster {
+            members,
+            similarity: similarities.get(&root).copied().unwrap_or_default(),
+        })
+        .collect()
+}
@@ -0,0 +1,207 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::Serialize;
+use tauri::AppHandle;
+use ureq::Agent;
+use url::Url;
+
+use crate::{
+    html, jobs, net,
+    types::{now, Snapshot},
+};
+
+pub const JOB_NAME: &str = "archive";
+
+pub const MAX_REDIRECTS: u32 = 10;
+
+const MAX_PAGE_BYTES: u64 = 10 * 1024 * 1024;
+const MAX_RESOURCE_BYTES: u64 = 5 * 1024 * 1024;
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+#[derive(Serialize, Clone)]
+pub struct ArchiveResult {
+    link_id: usize,
+    snapshot: Option<Snapshot>,
+    error: Option<String>,
+}
+
+pub fn snapshots_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path_resolver()
+        .app_data_dir()
+        .ok_or("Unable to find the app data directory")?
+        .join(SNAPSHOTS_DIR);
+
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    Ok(dir)
+}
+
+// Points every `url(...)` in a stylesheet at an absolute URL, since the sheet no
+// longer lives next to what it references once it's inlined
+fn absolutize_css_urls(css: &str, stylesheet_url: &Url) -> String {
+    let mut result = String::with_capacity(css.len());
+    let mut rest = css;
+
+    while let Some(start) = rest.find("url(") {
+        let Some(end) = rest[start..].find(')').map(|end| start + end) else {
+            break;
+        };
+
+        let reference = rest[start + 4..end].trim().trim_matches(['"', '\'']);
+
+        result.push_str(&rest[..start]);
+
+        match stylesheet_url.join(reference) {
+            Ok(url) if !reference.starts_with("data:") => {
+                result.push_str(&format!("url(\"{}\")", url));
+            }
+            _ => result.push_str(&rest[start..=end]),
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+
+    result
+}
+
+fn inline_resources(agent: &Agent, page: &str, base: &Url) -> String {
+    let page = html::replace_tags(page, "link", |attributes| {
+        let is_stylesheet = attributes.get("rel").is_some_and(|rel| {
+            rel.split_whitespace()
+                .any(|rel| rel.eq_ignore_ascii_case("stylesheet"))
+        });
+
+        if !is_stylesheet {
+            return None;
+        }
+
+        let href = base.join(attributes.get("href")?).ok()?;
+        let fetched = net::fetch(agent, href.as_str(), MAX_RESOURCE_BYTES).ok()?;
+
+        let css = absolutize_css_urls(&String::from_utf8_lossy(&fetched.body), &href);
+
+        Some(format!(
+            "<style>{}</style>",
+            css.replace("</style", "<\\/style")
+        ))
+    });
+
+    html::replace_tags(&page, "img", |attributes| {
+        let src = base.join(attributes.get("src")?).ok()?;
+        let fetched = net::fetch(agent, src.as_str(), MAX_RESOURCE_BYTES).ok()?;
+
+        if !fetched.content_type.starts_with("image/") {
+            return None;
+        }
+
+        let mut attributes = attributes.clone();
+
+        // Would otherwise take priority over the inlined source
+        attributes.remove("srcset");
+        attributes.insert(
+            "src".to_string(),
+            format!(
+                "data:{};base64,{}",
+                fetched.content_type,
+                BASE64.encode(&fetched.body)
+            ),
+        );
+
+        Some(html::render_tag("img", &attributes))
+    })
+}
+
+pub fn archive(
+    agent: &Agent,
+    dir: &Path,
+    link_id: usize,
+    uri: &str,
+    inline: bool,
+) -> Result<Snapshot, String> {
+    let base = Url::parse(uri).map_err(|e| e.to_string())?;
+    let mut page = net::fetch_html(agent, uri, MAX_PAGE_BYTES)?;
+
+    if inline {
+        page = inline_resources(agent, &page, &base);
+    }
+
+    // Keeps relative links and anything that wasn't inlined pointing at the original site
+    let page = html::prepend_to_head(
+        &page,
+        &format!("<base href=\"{}\">", html::escape_attribute(base.as_str())),
+    );
+
+    let created_at = now();
+
+    let mut hasher = DefaultHasher::new();
+    uri.hash(&mut hasher);
+
+    // Copies of a link can be archived at the same time, so each gets its own file
+    let path = dir.join(format!(
+        "{}-{}-{:016x}.html",
+        created_at,
+        link_id,
+        hasher.finish()
+    ));
+
+    fs::write(&path, &page).map_err(|e| e.to_string())?;
+
+    Ok(Snapshot {
+        path: path.to_string_lossy().into_owned(),
+        size: page.len() as u64,
+        created_at,
+        self_contained: inline,
+    })
+}
+
+// Replaces the link's snapshot, removing the file of the one it had before
+pub fn record(
+    app: &AppHandle,
+    link_id: usize,
+    archived: Result<Snapshot, String>,
+) -> ArchiveResult {
+    match archived {
+        Ok(snapshot) => {
+            jobs::update_link(app, link_id, |link| {
+                if let Some(previous) = link.snapshot.replace(snapshot.clone()) {
+                    fs::remove_file(previous.path).ok();
+                }
+            });
+
+            ArchiveResult {
+                link_id,
+                snapshot: Some(snapshot),
+                error: None,
+            }
+        }
+        Err(error) => ArchiveResult {
+            link_id,
+            snapshot: None,
+            error: Some(error),
+        },
+    }
+}
+
+// Every file in the snapshots directory along with its size
+pub fn stored_files(dir: &Path) -> Result<Vec<(PathBuf, u64)>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| e.to_string())?;
+
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+
+            metadata.is_file().then(|| (entry.path(), metadata.len()))
+        })
+        .collect())
+}
@@ -0,0 +1,314 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::types::{LinkGroups, Tags};
+
+// Tags form a hierarchy through their names, `dev/rust/async` sits under
+// `dev/rust` which sits under `dev`, without the parents having to exist
+pub const SEPARATOR: char = '/';
+
+#[derive(Serialize, Default)]
+pub struct TagNode {
+    name: String,
+    path: String,
+    // Whether the tag itself exists, rather than only being implied by a child
+    exists: bool,
+    children: Vec<TagNode>,
+}
+
+// Trims every level of the name and rejects empty ones, so `dev/ rust` and
+// `dev/rust` end up as the same tag
+pub fn normalize(name: &str) -> Result<String, String> {
+    let segments = name
+        .split(SEPARATOR)
+        .map(|segment| segment.trim())
+        .collect::<Vec<_>>();
+
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(format!("Invalid tag name: '{}'", name));
+    }
+
+    Ok(segments.join(&SEPARATOR.to_string()))
+}
+
+// Whether `tag` is `ancestor` itself or somewhere underneath it
+pub fn is_within(tag: &str, ancestor: &str) -> bool {
+    tag.strip_prefix(ancestor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(SEPARATOR))
+}
+
+// Moves `tag` from under `from` to under `to`, if it's within `from` at all
+pub fn move_within(tag: &str, from: &str, to: &str) -> Option<String> {
+    is_within(tag, from).then(|| format!("{}{}", to, &tag[from.len()..]))
+}
+
+// The tag an alias stands for, carrying over anything underneath the alias so
+// `js/react` becomes `javascript/react`
+pub fn resolve_alias(tags: &Tags, tag: &str) -> Option<String> {
+    if tags.contains_key(tag) {
+        return None;
+    }
+
+    tags.iter().find_map(|(name, info)| {
+        info.aliases
+            .iter()
+            .find_map(|alias| move_within(tag, alias, name))
+    })
+}
+
+pub fn resolve(tags: &Tags, tag: &str) -> String {
+    resolve_alias(tags, tag).unwrap_or_else(|| tag.to_owned())
+}
+
+// Adds the tags `into` doesn't have yet, keeping its order
+pub fn union(into: &mut Vec<String>, from: &[String]) {
+    for tag in from {
+        if !into.contains(tag) {
+            into.push(tag.to_owned());
+        }
+    }
+}
+
+// Applies `rewrite` to every tag, dropping any duplicates it creates while
+// keeping the order the tags were added in
+pub fn rewrite_tags<F>(tags: &mut Vec<String>, rewrite: F) -> bool
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut changed = false;
+    let mut rewritten = Vec::<String>::with_capacity(tags.len());
+
+    for tag in tags.iter() {
+        let tag = match rewrite(tag) {
+            Some(new_tag) => {
+                changed = true;
+                new_tag
+            }
+            None => tag.to_owned(),
+        };
+
+        if !rewritten.contains(&tag) {
+            rewritten.push(tag);
+        }
+    }
+
+    *tags = rewritten;
+
+    changed
+}
+
+#[derive(Serialize, Default)]
+pub struct TagIssues {
+    // In the tag list without any link using them, or anything underneath them
+    unused: Vec<String>,
+    // Used on links but missing from the tag list
+    missing: Vec<String>,
+}
+
+impl TagIssues {
+    pub fn find(link_groups: &LinkGroups, tags: &Tags) -> TagIssues {
+        let link_tags = link_groups
+            .iter()
+            .flat_map(|group| &group.links)
+            .flat_map(|link| &link.tags)
+            .collect::<BTreeSet<_>>();
+
+        TagIssues {
+            unused: tags
+                .keys()
+                .filter(|tag| !link_tags.iter().any(|link_tag| is_within(link_tag, tag)))
+                .cloned()
+                .collect(),
+            missing: link_tags
+                .into_iter()
+                .filter(|tag| !tags.contains_key(*tag))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    // Adds the missing tags to the list and, with `remove_unused`, removes the unused
+    // ones, leaving only what was changed
+    pub fn reconcile(mut self, tags: &mut Tags, remove_unused: bool) -> TagIssues {
+        for tag in self.missing.iter() {
+            tags.entry(tag.to_owned()).or_default();
+        }
+
+        if remove_unused {
+            for tag in self.unused.iter() {
+                tags.remove(tag);
+            }
+        } else {
+            self.unused.clear();
+        }
+
+        self
+    }
+}
+
+pub fn build_tree<'a>(tags: impl Iterator<Item = &'a String>) -> Vec<TagNode> {
+    #[derive(Default)]
+    struct Builder {
+        exists: bool,
+        children: BTreeMap<String, Builder>,
+    }
+
+    fn into_nodes(children: BTreeMap<String, Builder>, parent: Option<&str>) -> Vec<TagNode> {
+        children
+            .into_iter()
+            .map(|(name, builder)| {
+                let path = match parent {
+                    Some(parent) => format!("{}{}{}", parent, SEPARATOR, name),
+                    None => name.to_owned(),
+                };
+
+                TagNode {
+                    children: into_nodes(builder.children, Some(&path)),
+                    name,
+                    path,
+                    exists: builder.exists,
+                }
+            })
+            .collect()
+    }
+
+    let mut root = Builder::default();
+
+    for tag in tags {
+        let node = tag.split(SEPARATOR).fold(&mut root, |node, segment| {
+            node.children.entry(segment.to_string()).or_default()
+        });
+
+        node.exists = true;
+    }
+
+    into_nodes(root.children, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Link, LinkGroup, TagInfo};
+
+    fn tags(names: &[&str]) -> Tags {
+        names
+            .iter()
+            .map(|name| (name.to_string(), TagInfo::default()))
+            .collect()
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn normalizes_every_level() {
+        assert_eq!(normalize(" dev / rust "), Ok("dev/rust".to_string()));
+        assert!(normalize("dev//rust").is_err());
+        assert!(normalize("dev/").is_err());
+        assert!(normalize("  ").is_err());
+    }
+
+    #[test]
+    fn only_whole_levels_are_within() {
+        assert!(is_within("dev", "dev"));
+        assert!(is_within("dev/rust/async", "dev"));
+        assert!(!is_within("devops", "dev"));
+        assert!(!is_within("dev", "dev/rust"));
+    }
+
+    #[test]
+    fn moves_tags_along_with_their_ancestor() {
+        assert_eq!(
+            move_within("dev/rust", "dev", "code"),
+            Some("code/rust".to_string())
+        );
+        assert_eq!(move_within("dev", "dev", "code"), Some("code".to_string()));
+        assert_eq!(move_within("devops", "dev", "code"), None);
+    }
+
+    #[test]
+    fn resolves_aliases_and_what_is_underneath_them() {
+        let mut tags = tags(&["javascript", "js-tools"]);
+
+        tags.get_mut("javascript").unwrap().aliases = strings(&["js"]);
+
+        assert_eq!(resolve(&tags, "js"), "javascript");
+        assert_eq!(resolve(&tags, "js/react"), "javascript/react");
+        // Existing tags aren't taken for aliases, nor are names merely starting with one
+        assert_eq!(resolve(&tags, "js-tools"), "js-tools");
+        assert_eq!(resolve(&tags, "jsx"), "jsx");
+    }
+
+    #[test]
+    fn union_keeps_the_order_and_skips_duplicates() {
+        let mut into = strings(&["b", "a"]);
+
+        union(&mut into, &strings(&["a", "c", "b", "d"]));
+
+        assert_eq!(into, strings(&["b", "a", "c", "d"]));
+    }
+
+    #[test]
+    fn rewriting_drops_the_duplicates_it_creates() {
+        let mut link_tags = strings(&["code/rust", "dev/rust", "dev"]);
+
+        let changed = rewrite_tags(&mut link_tags, |tag| move_within(tag, "dev", "code"));
+
+        assert!(changed);
+        assert_eq!(link_tags, strings(&["code/rust", "code"]));
+        assert!(!rewrite_tags(&mut link_tags, |tag| move_within(
+            tag, "dev", "code"
+        )));
+    }
+
+    #[test]
+    fn finds_and_reconciles_unused_and_missing_tags() {
+        let link = Link::new(
+            "https://example.com".to_string(),
+            "Example".to_string(),
+            strings(&["dev/rust", "news"]),
+        );
+        let link_groups = LinkGroups::from([LinkGroup::new([link].into())]);
+
+        let mut tags = tags(&["dev", "dev/rust", "old"]);
+
+        let issues = TagIssues::find(&link_groups, &tags);
+
+        // Parents of used tags count as used
+        assert_eq!(issues.unused, strings(&["old"]));
+        assert_eq!(issues.missing, strings(&["news"]));
+
+        let issues = issues.reconcile(&mut tags, false);
+
+        assert!(issues.unused.is_empty());
+        assert!(tags.contains_key("news") && tags.contains_key("old"));
+
+        TagIssues::find(&link_groups, &tags).reconcile(&mut tags, true);
+
+        assert_eq!(
+            tags.keys().cloned().collect::<Vec<_>>(),
+            strings(&["dev", "dev/rust", "news"])
+        );
+    }
+
+    #[test]
+    fn builds_a_tree_with_implied_parents() {
+        let names = strings(&["dev/rust/async", "dev", "news"]);
+        let tree = build_tree(names.iter());
+
+        assert_eq!(tree.len(), 2);
+
+        let dev = &tree[0];
+
+        assert_eq!((dev.name.as_str(), dev.exists), ("dev", true));
+
+        let rust = &dev.children[0];
+
+        assert_eq!((rust.path.as_str(), rust.exists), ("dev/rust", false));
+        assert_eq!(rust.children[0].path, "dev/rust/async");
+        assert!(rust.children[0].exists);
+        assert_eq!(tree[1].name, "news");
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+// The parts of a request tests care about
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+}
+
+pub struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Response {
+    pub fn new(status: u16) -> Response {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    pub fn html(body: &str) -> Response {
+        Response::new(200)
+            .header("Content-Type", "text/html; charset=utf-8")
+            .body(body)
+    }
+
+    pub fn redirect(status: u16, location: &str) -> Response {
+        Response::new(status).header("Location", location)
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: &str) -> Response {
+        self.body = body.to_string();
+        self
+    }
+}
+
+// A local HTTP server answering every request with whatever `respond` returns, so
+// that network code can be tested without reaching the internet. Runs until the
+// test process exits
+pub struct TestServer {
+    address: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl TestServer {
+    pub fn start<F>(respond: F) -> TestServer
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Test server should bind");
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let respond = Arc::new(respond);
+
+        {
+            let requests = requests.clone();
+
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let requests = requests.clone();
+                    let respond = respond.clone();
+
+                    thread::spawn(move || serve(stream, &requests, &*respond));
+                }
+            });
+        }
+
+        TestServer { address, requests }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.address, path)
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn serve(
+    mut stream: TcpStream,
+    requests: &Mutex<Vec<Request>>,
+    respond: &(dyn Fn(&Request) -> Response + Send + Sync),
+) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut line = String::new();
+
+    if reader.read_line(&mut line).is_err() {
+        return;
+    }
+
+    let mut parts = line.split_whitespace();
+
+    let request = Request {
+        method: parts.next().unwrap_or_default().to_string(),
+        path: parts.next().unwrap_or_default().to_string(),
+    };
+
+    // Headers are skipped, requests from the app never have a body
+    loop {
+        line.clear();
+
+        match reader.read_line(&mut line) {
+            Ok(read) if read > 0 && line.trim().is_empty() => break,
+            Ok(read) if read > 0 => continue,
+            _ => return,
+        }
+    }
+
+    requests.lock().unwrap().push(request.clone());
+
+    let response = respond(&request);
+
+    let mut head = format!(
+        "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+
+    for (name, value) in response.headers.iter() {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).ok();
+
+    if request.method != "HEAD" {
+        stream.write_all(response.body.as_bytes()).ok();
+    }
+
+    stream.flush().ok();
+}
@@ -1,26 +1,99 @@
 use std::{
     cell::RefCell,
-    collections::{BTreeSet, VecDeque},
+    collections::{BTreeMap, VecDeque},
     sync::{
-        atomic::{AtomicUsize, Ordering},
-        Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
     },
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use serde::{Deserialize, Serialize};
+use url::Url;
 
-fn get_new_id() -> usize {
+use crate::{
+    canonical::CanonicalizeConfig,
+    folders::{Folder, StorableFolder},
+    history::History,
+    rules::{StorableTagRule, TagRule},
+};
+
+pub fn get_new_id() -> usize {
     static mut LAST_ID: AtomicUsize = AtomicUsize::new(0);
 
     unsafe { LAST_ID.fetch_add(1, Ordering::Release) }
 }
 
+// Milliseconds since the unix epoch, matching what the frontend's `Date` expects
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+// Locally cached details of the page a link points to, used for searching and
+// for replacing poor titles
+#[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug, Default)]
+pub struct PageContent {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub text: Option<String>,
+    pub og_title: Option<String>,
+    pub image_uri: Option<String>,
+    pub canonical_uri: Option<String>,
+    pub fetched_at: Option<u64>,
+}
+
+// Result of the last time the link checker requested a link
+#[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+pub struct LinkHealth {
+    pub status: Option<u16>,
+    // Only set when redirects led somewhere other than the link's URI
+    pub final_uri: Option<String>,
+    pub error: Option<String>,
+    pub checked_at: u64,
+}
+
+impl LinkHealth {
+    // Auth and rate limit statuses are left out since they usually mean the page
+    // is there but blocked us, not that it's gone
+    pub fn is_dead(&self) -> bool {
+        self.error.is_some()
+            || self
+                .status
+                .is_some_and(|status| matches!(status, 404 | 410 | 500..))
+    }
+}
+
+// An archived copy of the page, stored under the app data directory
+#[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+pub struct Snapshot {
+    pub path: String,
+    pub size: u64,
+    pub created_at: u64,
+    // Whether stylesheets and images were inlined, otherwise they're still loaded
+    // from the original site
+    pub self_contained: bool,
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
 pub struct Link {
     pub id: usize,
     pub uri: String,
     pub title: String,
     pub tags: Vec<String>,
+    pub content: Option<PageContent>,
+    pub created_at: Option<u64>,
+    // Last time the user changed the link itself, left unset until then
+    pub updated_at: Option<u64>,
+    pub last_opened_at: Option<u64>,
+    pub health: Option<LinkHealth>,
+    pub snapshot: Option<Snapshot>,
+    // URIs the link was previously saved under, such as short links it was expanded from
+    pub aliases: Vec<String>,
+    // Free-form Markdown written by the user
+    pub note: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
@@ -28,6 +101,22 @@ pub struct StorableLink {
     pub uri: String,
     pub title: String,
     pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<PageContent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_opened_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<LinkHealth>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<Snapshot>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 impl Link {
@@ -37,8 +126,57 @@ impl Link {
             uri,
             title,
             tags,
+            content: None,
+            created_at: Some(now()),
+            updated_at: None,
+            last_opened_at: None,
+            health: None,
+            snapshot: None,
+            aliases: Vec::new(),
+            note: None,
         }
     }
+
+    pub fn touch(&mut self) {
+        self.updated_at = Some(now());
+    }
+
+    // Everything fetched or learned about the old URI is dropped along with it, as
+    // it likely no longer describes the page
+    pub fn set_uri(&mut self, uri: String) {
+        if uri == self.uri {
+            return;
+        }
+
+        self.uri = uri;
+        self.aliases.clear();
+        self.health = None;
+        self.content = None;
+        self.snapshot = None;
+    }
+
+    // The current URI followed by any aliases
+    pub fn uris(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.uri).chain(self.aliases.iter())
+    }
+
+    pub fn domain(&self) -> &str {
+        let without_scheme = self
+            .uri
+            .split_once("://")
+            .map_or(self.uri.as_str(), |(_, rest)| rest);
+
+        let authority = without_scheme
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default();
+
+        let host = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+
+        host.split(':').next().unwrap_or_default()
+    }
 }
 
 impl From<StorableLink> for Link {
@@ -48,6 +186,14 @@ impl From<StorableLink> for Link {
             uri: value.uri,
             title: value.title,
             tags: value.tags,
+            content: value.content,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            last_opened_at: value.last_opened_at,
+            health: value.health,
+            snapshot: value.snapshot,
+            aliases: value.aliases,
+            note: value.note,
         }
     }
 }
@@ -58,6 +204,14 @@ impl From<Link> for StorableLink {
             uri: value.uri,
             title: value.title,
             tags: value.tags,
+            content: value.content,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            last_opened_at: value.last_opened_at,
+            health: value.health,
+            snapshot: value.snapshot,
+            aliases: value.aliases,
+            note: value.note,
         }
     }
 }
@@ -66,11 +220,45 @@ impl From<Link> for StorableLink {
 pub struct LinkGroup {
     pub id: usize,
     pub links: VecDeque<Link>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    pub created_at: Option<u64>,
+    // Last time the group's details or the links in it changed
+    pub updated_at: Option<u64>,
+    pub last_opened_at: Option<u64>,
+    // Pinned groups are kept above the rest
+    pub pinned: bool,
+    pub starred: bool,
+    // Locked groups can't be removed, reordered or have links taken out of them
+    pub locked: bool,
+    pub folder_id: Option<usize>,
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
 pub struct StorableLinkGroup {
     pub links: VecDeque<StorableLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_opened_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub starred: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    // Position of the folder in the saved folder list, as folder ids aren't kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<usize>,
 }
 
 impl LinkGroup {
@@ -78,15 +266,57 @@ impl LinkGroup {
         LinkGroup {
             id: get_new_id(),
             links,
+            title: None,
+            description: None,
+            color: None,
+            created_at: Some(now()),
+            updated_at: None,
+            last_opened_at: None,
+            pinned: false,
+            starred: false,
+            locked: false,
+            folder_id: None,
+        }
+    }
+
+    pub fn touch(&mut self) {
+        self.updated_at = Some(now());
+    }
+
+    pub fn check_unlocked(&self) -> Result<(), String> {
+        if !self.locked {
+            return Ok(());
+        }
+
+        match self.title {
+            Some(ref title) => Err(format!("Group '{}' is locked", title)),
+            None => Err(format!("Group {} is locked", self.id)),
         }
     }
 }
 
+// Moves pinned groups to the top, keeping the order within pinned and unpinned groups
+pub fn sort_pinned_first(link_groups: &mut LinkGroups) {
+    link_groups
+        .make_contiguous()
+        .sort_by_key(|group| !group.pinned);
+}
+
 impl From<StorableLinkGroup> for LinkGroup {
     fn from(value: StorableLinkGroup) -> Self {
         LinkGroup {
             id: get_new_id(),
             links: value.links.into_iter().map(|link| link.into()).collect(),
+            title: value.title,
+            description: value.description,
+            color: value.color,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            last_opened_at: value.last_opened_at,
+            pinned: value.pinned,
+            starred: value.starred,
+            locked: value.locked,
+            folder_id: None,
         }
     }
 }
@@ -95,17 +325,131 @@ impl From<LinkGroup> for StorableLinkGroup {
     fn from(value: LinkGroup) -> Self {
         StorableLinkGroup {
             links: value.links.into_iter().map(|link| link.into()).collect(),
+            title: value.title,
+            description: value.description,
+            color: value.color,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            last_opened_at: value.last_opened_at,
+            pinned: value.pinned,
+            starred: value.starred,
+            locked: value.locked,
+            folder: None,
         }
     }
 }
 
+// Accepts `#rgb` and `#rrggbb` colors
+pub fn is_valid_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+pub fn validate_title(title: &str) -> Result<String, String> {
+    match title.trim() {
+        "" => Err("Link title can't be empty".to_string()),
+        title => Ok(title.to_string()),
+    }
+}
+
+// URIs are kept as written, the parser is only used to reject ones that can't be opened
+pub fn validate_uri(uri: &str) -> Result<String, String> {
+    match uri.trim() {
+        "" => Err("Link URI can't be empty".to_string()),
+        uri => Url::parse(uri)
+            .map(|_| uri.to_string())
+            .map_err(|e| format!("Invalid URI '{}': {}", uri, e)),
+    }
+}
+
 pub type LinkGroups = VecDeque<LinkGroup>;
 pub type LinksContainer = Mutex<RefCell<LinkGroups>>;
 
-pub type TagsContainer = Mutex<RefCell<BTreeSet<String>>>;
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TagInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    // Other names that resolve to this tag when toggling, searching or importing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+impl TagInfo {
+    // Fields already set are kept, while aliases from both are combined
+    pub fn merge(&mut self, other: TagInfo) {
+        self.color = self.color.take().or(other.color);
+        self.description = self.description.take().or(other.description);
+        self.icon = self.icon.take().or(other.icon);
+
+        for alias in other.aliases {
+            if !self.aliases.contains(&alias) {
+                self.aliases.push(alias);
+            }
+        }
+    }
+}
+
+pub type Tags = BTreeMap<String, TagInfo>;
+pub type TagsContainer = Mutex<RefCell<Tags>>;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(from = "StoredTag")]
+pub struct StorableTag {
+    pub name: String,
+    #[serde(flatten)]
+    pub info: TagInfo,
+}
+
+// Older saves stored tags as a list of plain names
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredTag {
+    Name(String),
+    Record {
+        name: String,
+        #[serde(flatten)]
+        info: TagInfo,
+    },
+}
+
+impl From<StoredTag> for StorableTag {
+    fn from(value: StoredTag) -> Self {
+        match value {
+            StoredTag::Name(name) => StorableTag {
+                name,
+                info: TagInfo::default(),
+            },
+            StoredTag::Record { name, info } => StorableTag { name, info },
+        }
+    }
+}
+
+pub type CanonicalizeContainer = Mutex<RefCell<CanonicalizeConfig>>;
+
+pub type TagRulesContainer = Mutex<RefCell<Vec<TagRule>>>;
+
+// Locked after links, as folders are only meaningful alongside the groups in them
+pub type FoldersContainer = Mutex<RefCell<Vec<Folder>>>;
+
+// Locked after links and tags, as undoing replaces both
+pub type HistoryContainer = Mutex<RefCell<History>>;
+
+// Cancellation flags of the background jobs that are currently running, by name
+pub type JobsContainer = Mutex<RefCell<BTreeMap<String, Arc<AtomicBool>>>>;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct SaveData {
-    pub tags: Vec<String>,
+    pub tags: Vec<StorableTag>,
     pub link_groups: Vec<StorableLinkGroup>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonicalize: Option<CanonicalizeConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_rules: Vec<StorableTagRule>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<StorableFolder>,
 }
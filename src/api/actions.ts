@@ -1,6 +1,19 @@
 import { revalidate } from "@solidjs/router";
 import { invoke } from "@tauri-apps/api";
 import { ImportConfigDialogSubmission } from "@/components/ImportConfigDialog";
+import {
+    BulkOperation,
+    BulkResult,
+    LayoutStrategy,
+    ProposedGroup,
+    ReplaceQuery,
+    Replacement,
+    RuleMatcher,
+    RuleResult,
+    SplitMode,
+    TagInfo,
+    TagIssues,
+} from "@/types";
 import { ALL_CACHE_KEYS, CacheKeys } from "./fetchers";
 
 export async function removeEmptyGroups() {
@@ -9,6 +22,137 @@ export async function removeEmptyGroups() {
     await revalidate(CacheKeys.LINK_GROUPS);
 }
 
+export async function updateGroup(
+    id: number,
+    details: { title?: string; description?: string; color?: string },
+) {
+    await invoke("update_group", {
+        id,
+        ...details,
+    });
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS]);
+}
+
+export async function setGroupFlags(
+    id: number,
+    flags: { pinned?: boolean; starred?: boolean; locked?: boolean },
+) {
+    await invoke("set_group_flags", {
+        id,
+        ...flags,
+    });
+
+    await revalidate(CacheKeys.LINK_GROUPS);
+}
+
+export async function addFolder(name: string, parentId?: number) {
+    const id = (await invoke("add_folder", {
+        name,
+        parentId,
+    })) as number;
+
+    await revalidate(CacheKeys.LINK_GROUPS);
+
+    return id;
+}
+
+export async function renameFolder(id: number, name: string) {
+    await invoke("rename_folder", {
+        id,
+        name,
+    });
+
+    await revalidate(CacheKeys.LINK_GROUPS);
+}
+
+export async function moveFolder(
+    id: number,
+    parentId: number | undefined,
+    position: number,
+) {
+    await invoke("move_folder", {
+        id,
+        parentId,
+        position,
+    });
+
+    await revalidate(CacheKeys.LINK_GROUPS);
+}
+
+export async function removeFolder(id: number, removeGroups: boolean) {
+    await invoke("remove_folder", {
+        id,
+        removeGroups,
+    });
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS]);
+}
+
+export async function moveGroupToFolder(
+    groupId: number,
+    folderId: number | undefined,
+    position: number,
+) {
+    await invoke("move_group_to_folder", {
+        groupId,
+        folderId,
+        position,
+    });
+
+    await revalidate(CacheKeys.LINK_GROUPS);
+}
+
+export async function mergeGroups(groupIds: number[], dedupe: boolean) {
+    const id = (await invoke("merge_groups", {
+        groupIds,
+        dedupe,
+    })) as number;
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS, CacheKeys.DUPES]);
+
+    return id;
+}
+
+export async function splitGroup(id: number, mode: SplitMode) {
+    const ids = (await invoke("split_group", {
+        id,
+        mode,
+    })) as number[];
+
+    await revalidate(CacheKeys.LINK_GROUPS);
+
+    return ids;
+}
+
+export async function sortGroup(
+    id: number,
+    sortBy: "title" | "uri" | "domain" | "date_added" | "last_updated",
+    sortDirection: "asc" | "desc" = "asc",
+) {
+    await invoke("sort_group", {
+        id,
+        sortBy,
+        sortDirection,
+    });
+
+    await revalidate(CacheKeys.LINK_GROUPS);
+}
+
+export async function proposeLayout(strategy: LayoutStrategy) {
+    return (await invoke("propose_layout", {
+        strategy,
+    })) as ProposedGroup[];
+}
+
+export async function applyLayout(groups: ProposedGroup[]) {
+    await invoke("apply_layout", {
+        groups,
+    });
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS]);
+}
+
 export async function purge() {
     await invoke("purge_links");
 
@@ -43,6 +187,18 @@ export function exportOnetabDataToPath(path: string) {
     return invoke("export_for_onetab", { path });
 }
 
+export function exportNetscapeDataToPath(path: string) {
+    return invoke("export_as_netscape", { path });
+}
+
+export function exportChromiumDataToPath(path: string) {
+    return invoke("export_as_chromium", { path });
+}
+
+export function exportMarkdownDataToPath(path: string) {
+    return invoke("export_as_markdown", { path });
+}
+
 export async function removeLink(id: number) {
     await invoke("remove_link", {
         id,
@@ -50,6 +206,57 @@ export async function removeLink(id: number) {
     await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.DUPES]);
 }
 
+export async function setLinkNote(id: number, note: string | null) {
+    await invoke("set_link_note", {
+        id,
+        note,
+    });
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS]);
+}
+
+export async function markGroupOpened(id: number) {
+    await invoke("mark_group_opened", {
+        id,
+    });
+
+    await revalidate(CacheKeys.LINK_GROUPS);
+}
+
+export async function markLinkOpened(id: number) {
+    await invoke("mark_link_opened", {
+        id,
+    });
+
+    await revalidate(CacheKeys.LINKS);
+}
+
+export async function resolveAllDupes(
+    keep:
+        | "First"
+        | "Last"
+        | "InLargestGroup"
+        | "InSmallestGroup"
+        | "WithMostTags",
+) {
+    const summary = await invoke("resolve_all_dupes", {
+        keep,
+    });
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS, CacheKeys.DUPES]);
+
+    return summary;
+}
+
+export async function mergeLinks(keepId: number, ids: number[]) {
+    await invoke("merge_links", {
+        keepId,
+        ids,
+    });
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS, CacheKeys.DUPES]);
+}
+
 export async function removeGroup(id: number) {
     await invoke("remove_group", {
         id,
@@ -62,9 +269,92 @@ export async function removeTag(name: string) {
         name,
     });
 
+    await revalidate([CacheKeys.TAGS, CacheKeys.LINKS, CacheKeys.LINK_GROUPS]);
+}
+
+export async function setTagInfo(name: string, info: TagInfo) {
+    await invoke("set_tag_info", {
+        name,
+        info,
+    });
+
     await revalidate(CacheKeys.TAGS);
 }
 
+export async function addTagRule(matcher: RuleMatcher, tags: string[]) {
+    await invoke("add_tag_rule", {
+        matcher,
+        ruleTags: tags,
+    });
+
+    await revalidate(CacheKeys.TAGS);
+}
+
+export async function updateTagRule(
+    id: number,
+    matcher: RuleMatcher,
+    tags: string[],
+    enabled: boolean,
+) {
+    await invoke("update_tag_rule", {
+        id,
+        matcher,
+        ruleTags: tags,
+        enabled,
+    });
+
+    await revalidate(CacheKeys.TAGS);
+}
+
+export async function removeTagRule(id: number) {
+    await invoke("remove_tag_rule", {
+        id,
+    });
+
+    await revalidate(CacheKeys.TAGS);
+}
+
+export async function applyTagRules(ruleIds?: number[], dryRun = false) {
+    const result = (await invoke("apply_tag_rules", {
+        ruleIds,
+        dryRun,
+    })) as RuleResult[];
+
+    if (!dryRun) {
+        await revalidate([CacheKeys.TAGS, CacheKeys.LINKS, CacheKeys.LINK_GROUPS]);
+    }
+
+    return result;
+}
+
+export async function reconcileTags(removeUnused: boolean) {
+    const result = (await invoke("reconcile_tags", {
+        removeUnused,
+    })) as TagIssues;
+
+    await revalidate(CacheKeys.TAGS);
+
+    return result;
+}
+
+export async function renameTag(from: string, to: string) {
+    await invoke("rename_tag", {
+        from,
+        to,
+    });
+
+    await revalidate([CacheKeys.TAGS, CacheKeys.LINKS, CacheKeys.LINK_GROUPS]);
+}
+
+export async function mergeTags(sources: string[], target: string) {
+    await invoke("merge_tags", {
+        sources,
+        target,
+    });
+
+    await revalidate([CacheKeys.TAGS, CacheKeys.LINKS, CacheKeys.LINK_GROUPS]);
+}
+
 export async function addLink(groupId: number, uri: string, title: string) {
     await invoke("add_link", {
         groupId,
@@ -75,6 +365,39 @@ export async function addLink(groupId: number, uri: string, title: string) {
     await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS, CacheKeys.DUPES]);
 }
 
+export async function updateLink(
+    id: number,
+    changes: { title?: string; uri?: string },
+) {
+    const duplicateIds = (await invoke("update_link", {
+        id,
+        title: changes.title ?? null,
+        uri: changes.uri ?? null,
+    })) as number[];
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS, CacheKeys.DUPES]);
+
+    return duplicateIds;
+}
+
+export async function findAndReplace(
+    query: ReplaceQuery,
+    linkIds: number[] | null,
+    dryRun: boolean,
+) {
+    const replacements = (await invoke("find_and_replace", {
+        query,
+        linkIds,
+        dryRun,
+    })) as Replacement[];
+
+    if (!dryRun) {
+        await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS, CacheKeys.DUPES]);
+    }
+
+    return replacements;
+}
+
 export async function addGroup() {
     await invoke("add_group");
 
@@ -89,11 +412,144 @@ export async function addTag(name: string) {
     await revalidate(CacheKeys.TAGS);
 }
 
-export async function toggleTagForLink(tag: string, uri: string) {
-    await invoke("toggle_tag", {
-        linkUri: uri,
+export async function toggleTagForLink(
+    tag: string,
+    linkId: number,
+    allCopies = false,
+) {
+    const tags = (await invoke("toggle_tag", {
+        linkId,
         tagName: tag,
-    });
+        allCopies,
+    })) as string[];
 
     revalidate(CacheKeys.TAGS);
+
+    return tags;
+}
+
+export function checkLinks(ids?: number[]) {
+    return invoke("check_links", {
+        ids,
+    }) as Promise<number>;
+}
+
+export function cancelJob(name: string) {
+    return invoke("cancel_job", {
+        name,
+    });
+}
+
+export async function removeDeadLinks() {
+    const removed = await invoke("remove_dead_links");
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS, CacheKeys.DUPES]);
+
+    return removed as number;
+}
+
+export async function tagDeadLinks(tagName: string) {
+    const tagged = await invoke("tag_dead_links", {
+        tagName,
+    });
+
+    await revalidate([CacheKeys.LINKS, CacheKeys.TAGS]);
+
+    return tagged as number;
+}
+
+export function fetchMetadata(ids?: number[], lowQualityOnly?: boolean) {
+    return invoke("fetch_metadata", {
+        ids,
+        lowQualityOnly,
+    }) as Promise<number>;
+}
+
+export async function replaceLowQualityTitles(preview?: boolean) {
+    const replacements = await invoke("replace_low_quality_titles", {
+        preview,
+    });
+
+    if (!preview) {
+        await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS]);
+    }
+
+    return replacements as {
+        link_id: number;
+        old_title: string;
+        new_title: string;
+    }[];
+}
+
+export function archiveLinks(ids?: number[], inline?: boolean) {
+    return invoke("archive_links", {
+        ids,
+        inline,
+    }) as Promise<number>;
+}
+
+export function openSnapshot(id: number) {
+    return invoke("open_snapshot", {
+        id,
+    });
+}
+
+export async function deleteSnapshots(ids: number[]) {
+    const result = await invoke("delete_snapshots", {
+        ids,
+    });
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS]);
+
+    return result as { removed_files: number; freed_bytes: number };
+}
+
+export async function pruneSnapshots(
+    olderThan?: number,
+    removeOrphans?: boolean,
+) {
+    const result = await invoke("prune_snapshots", {
+        olderThan,
+        removeOrphans,
+    });
+
+    await revalidate([CacheKeys.LINK_GROUPS, CacheKeys.LINKS]);
+
+    return result as { removed_files: number; freed_bytes: number };
+}
+
+export function fetchFavicons(force?: boolean) {
+    return invoke("fetch_favicons", {
+        force,
+    }) as Promise<number>;
+}
+
+export function clearFaviconCache() {
+    return invoke("clear_favicon_cache");
+}
+
+export function expandRedirects(ids?: number[], apply?: boolean) {
+    return invoke("expand_redirects", {
+        ids,
+        apply,
+    }) as Promise<number>;
+}
+
+export async function bulkEditLinks(linkIds: number[], operation: BulkOperation) {
+    const result = (await invoke("bulk_edit_links", {
+        linkIds,
+        operation,
+    })) as BulkResult;
+
+    await revalidate(ALL_CACHE_KEYS);
+
+    return result;
+}
+
+export async function undo() {
+    const label = (await invoke("undo")) as string;
+
+    await revalidate(ALL_CACHE_KEYS);
+
+    return label;
 }
@@ -1,6 +1,20 @@
 import { cache } from "@solidjs/router";
 import { invoke } from "@tauri-apps/api";
-import { Link, LinkGroup, ResolveDupeItem } from "@/types";
+import {
+    DateFilter,
+    Folder,
+    Link,
+    LinkGroup,
+    NearDupeCluster,
+    ResolveDupeItem,
+    SearchResult,
+    StorableTag,
+    TagFilter,
+    TagIssues,
+    TagNode,
+    TagRule,
+    TagStats,
+} from "@/types";
 
 export const CacheKeys = {
     LINK_GROUPS: "link_groups",
@@ -12,7 +26,16 @@ export const CacheKeys = {
 export const ALL_CACHE_KEYS = Object.values(CacheKeys);
 
 export const getLinkGroups = cache(
-    () => invoke("get_links") as Promise<LinkGroup[]>,
+    (options?: {
+        sortBy?:
+            | "group_order"
+            | "title"
+            | "date_added"
+            | "last_updated"
+            | "last_opened";
+        sortDirection?: "asc" | "desc";
+        dateFilter?: DateFilter;
+    }) => invoke("get_links", { ...options }) as Promise<LinkGroup[]>,
     CacheKeys.LINK_GROUPS,
 );
 
@@ -21,6 +44,41 @@ export const getTags = cache(
     CacheKeys.TAGS,
 );
 
+export const getTagInfo = cache(
+    () => invoke("get_tag_info") as Promise<StorableTag[]>,
+    CacheKeys.TAGS,
+);
+
+export const getFolders = cache(
+    () => invoke("get_folders") as Promise<Folder[]>,
+    CacheKeys.LINK_GROUPS,
+);
+
+export const getHistory = cache(
+    () => invoke("get_history") as Promise<string[]>,
+    CacheKeys.LINK_GROUPS,
+);
+
+export const getTagRules = cache(
+    () => invoke("get_tag_rules") as Promise<TagRule[]>,
+    CacheKeys.TAGS,
+);
+
+export const getTagStats = cache(
+    () => invoke("get_tag_stats") as Promise<TagStats[]>,
+    CacheKeys.TAGS,
+);
+
+export const getTagIssues = cache(
+    () => invoke("find_tag_issues") as Promise<TagIssues>,
+    CacheKeys.TAGS,
+);
+
+export const getTagTree = cache(
+    () => invoke("get_tag_tree") as Promise<TagNode[]>,
+    CacheKeys.TAGS,
+);
+
 export const getTagsForLink = cache(
     (id: number) => invoke("get_tags_for_link", { id }) as Promise<string[]>,
     CacheKeys.TAGS,
@@ -31,24 +89,38 @@ export const getDupes = cache(
     CacheKeys.DUPES,
 );
 
+export const getNearDupes = cache(
+    (threshold?: number) =>
+        invoke("find_near_dupes", { threshold }) as Promise<NearDupeCluster[]>,
+    CacheKeys.DUPES,
+);
+
+export const getDeadLinks = cache(
+    () => invoke("get_dead_links") as Promise<Link[]>,
+    CacheKeys.LINKS,
+);
+
 export const searchLinks = cache(
     ({
         searchText,
         sortBy,
         sortDirection,
-        tag,
+        tagFilter,
+        dateFilter,
     }: {
         searchText: string | undefined;
         sortBy: string | undefined;
         sortDirection: "asc" | "desc" | undefined;
-        tag: string | undefined;
+        tagFilter: TagFilter | undefined;
+        dateFilter?: DateFilter;
     }) => {
         return invoke("search", {
             searchText,
-            sortBy: sortBy ?? "name",
+            sortBy: sortBy ?? "title",
             sortDirection: sortDirection ?? "asc",
-            tag,
-        }) as Promise<Link[]>;
+            tagFilter,
+            dateFilter,
+        }) as Promise<SearchResult>;
     },
     CacheKeys.LINKS,
 );
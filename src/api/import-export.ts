@@ -5,6 +5,9 @@ import createImportConfigDialog, {
 } from "@/components/ImportConfigDialog";
 import {
     duplicatesExistInFile,
+    exportChromiumDataToPath,
+    exportMarkdownDataToPath,
+    exportNetscapeDataToPath,
     exportOnetabDataToPath,
     importFromFile,
     saveDataToPath,
@@ -82,26 +85,31 @@ export function createImportExportFunctions({
         });
     }
 
-    async function exportForOnetab() {
-        const path = await dialog.save({
-            title: "Pick an export location",
-        });
+    function exportWith(exportToPath: (path: string) => Promise<unknown>) {
+        return async () => {
+            const path = await dialog.save({
+                title: "Pick an export location",
+            });
 
-        if (!path) {
-            return;
-        }
+            if (!path) {
+                return;
+            }
 
-        await exportOnetabDataToPath(path);
+            await exportToPath(path);
 
-        dialog.message("Export successful", {
-            title: "Success",
-            type: "info",
-        });
+            dialog.message("Export successful", {
+                title: "Success",
+                type: "info",
+            });
+        };
     }
 
     return {
         importData,
         saveData,
-        exportForOnetab,
+        exportForOnetab: exportWith(exportOnetabDataToPath),
+        exportAsNetscape: exportWith(exportNetscapeDataToPath),
+        exportAsChromium: exportWith(exportChromiumDataToPath),
+        exportAsMarkdown: exportWith(exportMarkdownDataToPath),
     };
 }
@@ -33,7 +33,7 @@ function TagAssignTable(props: {
     );
 
     async function toggleTag(tag: string) {
-        await toggleTagForLink(tag, props.data.uri);
+        await toggleTagForLink(tag, props.data.id);
         await refetch();
     }
 
@@ -7,10 +7,16 @@ import createImportConfigDialog from "@/components/ImportConfigDialog";
 export default function Header() {
     const { ImportConfigDialog, promptUser } = createImportConfigDialog();
 
-    const { importData, saveData, exportForOnetab } =
-        createImportExportFunctions({
-            promptUser,
-        });
+    const {
+        importData,
+        saveData,
+        exportForOnetab,
+        exportAsNetscape,
+        exportAsChromium,
+        exportAsMarkdown,
+    } = createImportExportFunctions({
+        promptUser,
+    });
 
     return (
         <>
@@ -31,6 +37,18 @@ export default function Header() {
                     <Button onClick={exportForOnetab} color="darkRed" rounded>
                         Export For Onetab
                     </Button>
+
+                    <Button onClick={exportAsNetscape} color="darkRed" rounded>
+                        Export As HTML Bookmarks
+                    </Button>
+
+                    <Button onClick={exportAsChromium} color="darkRed" rounded>
+                        Export For Chromium
+                    </Button>
+
+                    <Button onClick={exportAsMarkdown} color="darkRed" rounded>
+                        Export As Markdown
+                    </Button>
                 </div>
             </header>
 
@@ -11,13 +11,14 @@ import {
 } from "solid-js";
 import { type SetStoreFunction, produce } from "solid-js/store";
 import { Portal } from "solid-js/web";
-import { removeGroup, removeLink } from "@/api/actions";
+import { markLinkOpened, removeGroup, removeLink } from "@/api/actions";
 import createAddLinkDialog from "@/components/AddLinkDialog";
 import Button from "@/components/Button";
 import createTagAssignDialog from "@/components/TagAssignDialog";
 import type { Link, LinkGroup } from "@/types";
 import getLinkHeight from "@/utils/getLinkHeight";
 import createAutoScroller from "@/utils/createAutoScroller";
+import getFaviconUrl from "@/utils/getFaviconUrl";
 import { invoke } from "@tauri-apps/api";
 import { CacheKeys } from "@/api/fetchers";
 import { revalidate } from "@solidjs/router";
@@ -296,7 +297,20 @@ function GroupedTableItemLink(props: { link: Link }) {
                     onLinkDragStart(e, props.link, draggedData, setDraggedData)
                 }
             >
-                <div class="flex min-w-0 flex-1 flex-shrink-0 items-center overflow-hidden text-ellipsis whitespace-nowrap border-r border-gray-600 p-3">
+                <div class="flex min-w-0 flex-1 flex-shrink-0 items-center gap-2 overflow-hidden text-ellipsis whitespace-nowrap border-r border-gray-600 p-3">
+                    <Show when={getFaviconUrl(props.link.uri)}>
+                        {(src) => (
+                            <img
+                                class="h-4 w-4 flex-shrink-0"
+                                src={src()}
+                                alt=""
+                                loading="lazy"
+                                onError={(e) =>
+                                    (e.currentTarget.style.visibility = "hidden")
+                                }
+                            />
+                        )}
+                    </Show>
                     {props.link.title}
                 </div>
                 <div class="flex items-center justify-center gap-3 p-3">
@@ -304,6 +318,7 @@ function GroupedTableItemLink(props: { link: Link }) {
                         class="rounded bg-blue-600 px-3 py-2 hover:bg-blue-400"
                         href={props.link.uri}
                         target="_blank"
+                        onClick={() => markLinkOpened(props.link.id)}
                     >
                         Open
                     </a>
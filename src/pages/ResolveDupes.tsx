@@ -4,7 +4,6 @@ import {
     Ref,
     Show,
     createEffect,
-    createSelector,
     createSignal,
 } from "solid-js";
 import { removeLink } from "@/api/actions";
@@ -24,6 +23,7 @@ export default function ResolveDupes() {
         dupes_left: 0,
         groups: [],
         key: "",
+        link_ids: [],
     };
 
     return <ResolveDupeTable result={result() ?? fallbackResult} />;
@@ -64,7 +64,7 @@ function DupeList(props: {
     ref: Ref<HTMLDivElement>;
     result: ResolveDupeItem;
 }) {
-    const isDuplicatedKey = createSelector(() => props.result.key);
+    const isDuplicatedLink = (id: number) => props.result.link_ids.includes(id);
 
     async function removeLinkById(id: number) {
         removeLink(id);
@@ -84,8 +84,8 @@ function DupeList(props: {
                                         <li
                                             class="flex border-b border-gray-600 last-of-type:border-b-0"
                                             classList={{
-                                                "bg-red-500": isDuplicatedKey(
-                                                    d().uri,
+                                                "bg-red-500": isDuplicatedLink(
+                                                    d().id,
                                                 ),
                                             }}
                                         >
@@ -103,10 +103,9 @@ function DupeList(props: {
                                             </div>
                                             <div class="flex w-20 items-center justify-center px-3 py-1">
                                                 <Show
-                                                    when={
-                                                        d().uri ==
-                                                        props.result.key
-                                                    }
+                                                    when={isDuplicatedLink(
+                                                        d().id,
+                                                    )}
                                                 >
                                                     <Button
                                                         onClick={() =>
@@ -6,11 +6,12 @@ import {
 } from "@tanstack/solid-virtual";
 import { For, Show, onMount } from "solid-js";
 import { Portal } from "solid-js/web";
-import { removeLink } from "@/api/actions";
+import { markLinkOpened, removeLink } from "@/api/actions";
 import { CacheKeys } from "@/api/fetchers";
 import Button from "@/components/Button";
 import createTagAssignDialog from "@/components/TagAssignDialog";
 import type { Link, SortData } from "@/types";
+import getFaviconUrl from "@/utils/getFaviconUrl";
 import getLinkHeight from "@/utils/getLinkHeight";
 
 const ROW_HEIGHT = getLinkHeight() + 1;
@@ -118,7 +119,22 @@ function SortTableItem(props: {
         <>
             <tr ref={item} data-index={props.virtualItem.index}>
                 <td class="items-center overflow-hidden border border-gray-600 p-3">
-                    <div class="text-ellipsis ">{props.data.title}</div>
+                    <div class="flex items-center gap-2 text-ellipsis">
+                        <Show when={getFaviconUrl(props.data.uri)}>
+                            {(src) => (
+                                <img
+                                    class="h-4 w-4 flex-shrink-0"
+                                    src={src()}
+                                    alt=""
+                                    loading="lazy"
+                                    onError={(e) =>
+                                        (e.currentTarget.style.visibility = "hidden")
+                                    }
+                                />
+                            )}
+                        </Show>
+                        {props.data.title}
+                    </div>
                 </td>
                 <td
                     class="max-w-fit border border-gray-600 p-3 text-center"
@@ -130,6 +146,7 @@ function SortTableItem(props: {
                         <a
                             href={props.data.uri}
                             target="_blank"
+                            onClick={() => markLinkOpened(props.data.id)}
                             class="w-full whitespace-nowrap rounded bg-blue-600 px-3 py-2 hover:bg-blue-400 lg:w-fit"
                         >
                             Open
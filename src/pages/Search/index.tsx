@@ -16,7 +16,7 @@ function searchLinksViaParams(params: Partial<Params>) {
         searchText: params.searchText,
         sortBy: params.sortBy,
         sortDirection: params.sortDirection as "asc" | "desc",
-        tag: params.tag,
+        tagFilter: params.tag ? { all: [params.tag] } : undefined,
     });
 }
 
@@ -28,7 +28,8 @@ export function SearchData({ params }: RoutePreloadFuncArgs) {
 export default function Search() {
     const [params, setParams] = useSearchParams();
 
-    const links = createAsync(() => searchLinksViaParams(params));
+    const result = createAsync(() => searchLinksViaParams(params));
+    const links = () => result()?.links;
     const tags = createAsync(() => getTags());
 
     function setSearchText(searchText: string) {
@@ -1,8 +1,180 @@
 export type Predicate<T> = (item: T) => boolean;
 
-export type LinkGroup = { id: number; links: Link[] };
+export type LinkGroup = {
+    id: number;
+    links: Link[];
+    title: string | null;
+    description: string | null;
+    color: string | null;
+    created_at: number | null;
+    updated_at: number | null;
+    last_opened_at: number | null;
+    pinned: boolean;
+    starred: boolean;
+    locked: boolean;
+    folder_id: number | null;
+};
+
+export type SplitMode =
+    | { type: "at_position"; position: number }
+    | { type: "chunks"; size: number }
+    | { type: "domain" };
+
+export type LayoutStrategy =
+    | { type: "domain" }
+    | { type: "top_level_tag" }
+    | { type: "similar_titles"; threshold: number | null };
+
+export type ProposedGroup = {
+    title: string;
+    link_ids: number[];
+    group_id?: number | null;
+};
+
+export type Folder = {
+    id: number;
+    name: string;
+    parent_id: number | null;
+};
+
+export type PageContent = {
+    title: string | null;
+    description: string | null;
+    text: string | null;
+    og_title: string | null;
+    image_uri: string | null;
+    canonical_uri: string | null;
+    fetched_at: number | null;
+};
+
+export type LinkHealth = {
+    status: number | null;
+    final_uri: string | null;
+    error: string | null;
+    checked_at: number;
+};
+
+export type Snapshot = {
+    path: string;
+    size: number;
+    created_at: number;
+    self_contained: boolean;
+};
+
+export type Link = {
+    id: number;
+    uri: string;
+    title: string;
+    tags: string[];
+    content: PageContent | null;
+    created_at: number | null;
+    updated_at: number | null;
+    last_opened_at: number | null;
+    health: LinkHealth | null;
+    snapshot: Snapshot | null;
+    aliases: string[];
+    note: string | null;
+};
+
+export type TagInfo = {
+    color?: string;
+    description?: string;
+    icon?: string;
+    aliases?: string[];
+};
+
+export type StorableTag = TagInfo & {
+    name: string;
+};
+
+export type RuleMatcher =
+    | { type: "domain"; value: string }
+    | { type: "glob"; value: string }
+    | { type: "regex"; value: string }
+    | { type: "title_keyword"; value: string }
+    | { type: "group"; value: number };
+
+export type TagRule = {
+    id: number;
+    matcher: RuleMatcher;
+    tags: string[];
+    enabled: boolean;
+};
+
+export type RuleResult = {
+    rule_id: number;
+    link_ids: number[];
+};
+
+export type BulkOperation =
+    | { type: "remove" }
+    | { type: "move"; group_id: number; position?: number }
+    | { type: "copy"; group_id: number; position?: number }
+    | { type: "add_tags"; tags: string[] }
+    | { type: "remove_tags"; tags: string[] };
+
+export type BulkResult = {
+    succeeded: number[];
+    failed: { id: number; error: string }[];
+};
+
+export type ReplaceField = "title" | "uri";
+
+export type ReplaceQuery = {
+    pattern: string;
+    replacement: string;
+    regex: boolean;
+    case_sensitive: boolean;
+    fields: ReplaceField[];
+};
+
+export type Replacement = {
+    id: number;
+    field: ReplaceField;
+    before: string;
+    after: string;
+    error: string | null;
+    duplicate_ids: number[];
+};
 
-export type Link = { id: number; uri: string; title: string; tags: string[] };
+export type TagStats = {
+    name: string;
+    link_count: number;
+    group_count: number;
+    last_used_at: number | null;
+    listed: boolean;
+};
+
+export type TagIssues = {
+    unused: string[];
+    missing: string[];
+};
+
+export type TagNode = {
+    name: string;
+    path: string;
+    exists: boolean;
+    children: TagNode[];
+};
+
+export type TagFilter = {
+    all?: string[];
+    any?: string[];
+    none?: string[];
+    untagged?: boolean;
+};
+
+export type DateFilter = {
+    field: "created" | "updated" | "last_opened";
+    of?: "link" | "group";
+    after: number | null;
+    before: number | null;
+};
+
+export type SearchResult = {
+    links: Link[];
+    tag_counts: Record<string, number>;
+};
 
 export type SortData = {
     column: keyof Link;
@@ -18,5 +190,12 @@ export type ResolveDupeItemRow = {
 export type ResolveDupeItem = {
     dupes_left: number;
     key: string;
+    link_ids: number[];
+    groups: LinkGroup[];
+};
+
+export type NearDupeCluster = {
+    similarity: number;
+    link_ids: number[];
     groups: LinkGroup[];
 };
@@ -0,0 +1,15 @@
+// Icons are served from the backend's cache through the `icon` protocol, which
+// Windows webviews only expose as `https://icon.localhost/<host>`
+export default function getFaviconUrl(uri: string) {
+    let host: string;
+
+    try {
+        host = new URL(uri).hostname;
+    } catch {
+        return undefined;
+    }
+
+    return navigator.userAgent.includes("Windows")
+        ? `https://icon.localhost/${host}`
+        : `icon://${host}`;
+}